@@ -0,0 +1,351 @@
+//! Async wrappers over the blocking client API, enabled by the `async` feature.
+//!
+//! Every memory access still goes through `ReadProcessMemory`; here it runs on
+//! tokio's blocking pool so one task can drive many clients without stalling
+//! the runtime, and waits sleep with `tokio::time` instead of the thread.
+
+use tokio::task::spawn_blocking;
+use tokio::time::{sleep, Instant};
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::client::{Client, WizWalkerClient};
+use crate::client_handler::{ClientChanges, ClientHandler, HandledClient, ToolhelpEnumerator};
+use crate::error::{Result, WizWalkerError};
+use crate::file_readers::wad::Wad;
+use crate::memory::memory_objects::actor_body::ActorBody;
+use crate::memory::memory_objects::duel::Duel;
+use crate::memory::memory_objects::game_stats::GameStats;
+use crate::memory::memory_objects::window::Window;
+use crate::utils::XYZ;
+
+
+/// How often `wait_for_zone_change` checks the player object.
+pub const ZONE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+
+/// Runs blocking work on tokio's blocking pool.
+pub async fn run_blocking<T, F>(work: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    spawn_blocking(work).await
+        .map_err(|error| WizWalkerError::TaskFailed(error.to_string()))?
+}
+
+
+/// Calls `check` every `interval` until it returns a value.
+///
+/// Wrap it in `tokio::time::timeout` to give up eventually.
+pub async fn poll_until<T, F, Fut>(mut check: F, interval: Duration) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>>>,
+{
+    loop {
+        if let Some(value) = check().await? {
+            return Ok(value)
+        }
+
+        sleep(interval).await;
+    }
+}
+
+
+/// A `Client` shared between tasks.
+///
+/// Clones refer to the same client. Calls on one client run one at a time;
+/// calls on different clients run concurrently.
+#[derive(Clone)]
+pub struct AsyncClient {
+    process_id: u32,
+    client: Arc<Mutex<Client>>,
+}
+
+impl AsyncClient {
+    pub fn new(client: Client) -> Self {
+        Self {
+            process_id: client.process_id(),
+            client: Arc::new(Mutex::new(client)),
+        }
+    }
+
+    pub async fn attach(process_id: u32) -> Result<Self> {
+        run_blocking(move || Client::attach(process_id)).await.map(Self::new)
+    }
+
+    pub fn process_id(&self) -> u32 {
+        self.process_id
+    }
+
+    /// Runs `action` against the client on the blocking pool.
+    pub async fn run<T, F>(&self, action: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Client) -> Result<T> + Send + 'static,
+    {
+        let client = self.client.clone();
+        run_blocking(move || action(&mut *client.lock()?)).await
+    }
+
+    pub async fn is_running(&self) -> Result<bool> {
+        self.run(|client| client.is_running()).await
+    }
+
+    pub async fn detach(&self) -> Result<()> {
+        self.run(Client::detach).await
+    }
+
+    pub async fn activate_hooks(&self) -> Result<()> {
+        self.run(Client::activate_hooks).await
+    }
+
+    pub async fn deactivate_hooks(&self) -> Result<()> {
+        self.run(Client::deactivate_hooks).await
+    }
+
+    pub async fn activate_hook(&self, name: &str) -> Result<()> {
+        let name = name.to_string();
+        self.run(move |client| client.activate_hook(&name)).await
+    }
+
+    pub async fn deactivate_hook(&self, name: &str) -> Result<()> {
+        let name = name.to_string();
+        self.run(move |client| client.deactivate_hook(&name)).await
+    }
+
+    pub async fn player_base(&self) -> Result<usize> {
+        self.run(|client| client.player_base()).await
+    }
+
+    pub async fn body(&self) -> Result<ActorBody> {
+        self.run(|client| client.body()).await
+    }
+
+    pub async fn stats(&self) -> Result<GameStats> {
+        self.run(|client| client.stats()).await
+    }
+
+    pub async fn duel(&self) -> Result<Duel> {
+        self.run(|client| client.duel()).await
+    }
+
+    pub async fn quest_position(&self) -> Result<XYZ> {
+        self.run(|client| client.quest_position()).await
+    }
+
+    pub async fn teleport(&self, position: XYZ) -> Result<()> {
+        self.run(move |client| client.teleport(position)).await
+    }
+
+    pub async fn root_window(&self) -> Result<Window> {
+        self.run(|client| client.root_window()).await
+    }
+
+    pub async fn ui_scale(&self) -> Result<f32> {
+        self.run(|client| client.ui_scale()).await
+    }
+
+    pub async fn screen_size(&self) -> Result<(i32, i32)> {
+        self.run(|client| client.screen_size()).await
+    }
+
+    /// Waits for hook `name` to export a non-null value.
+    ///
+    /// Unlike `HookRegistry::wait_for_hook_value`, the client is only locked
+    /// for each read, so other tasks can use it in between.
+    pub async fn wait_for_hook_value(&self, name: &str, interval: Duration, timeout: Duration) -> Result<usize> {
+        let started = Instant::now();
+
+        loop {
+            let name = name.to_string();
+            let read = self.run(move |client| {
+                client.hooks().wait_for_hook_value(&name, Duration::ZERO, Duration::ZERO)
+            }).await;
+
+            match read {
+                Err(WizWalkerError::HookTimeout { address, .. }) => {
+                    let elapsed = started.elapsed();
+                    if elapsed >= timeout {
+                        return Err(WizWalkerError::HookTimeout { address, timeout })
+                    }
+
+                    sleep(interval.min(timeout - elapsed)).await;
+                }
+                result => return result
+            }
+        }
+    }
+
+    /// Waits for the player to load into another zone and returns their new actor body.
+    ///
+    /// Treats a new address from the player hook as a zone change, and keeps
+    /// waiting while the hook has no player. This assumes the client builds a
+    /// new player object for each zone it loads, the same assumption
+    /// `CurrentClientObject` makes by re-reading its address; it has not been
+    /// confirmed for every kind of zone load, so the wait gives up with
+    /// `ZoneChangeTimeout` after `timeout`.
+    pub async fn wait_for_zone_change(&self, timeout: Duration) -> Result<usize> {
+        let current_player = self.player_base().await?;
+
+        let changed = poll_until(|| async move {
+            match self.player_base().await {
+                // No player while the next zone loads.
+                Err(WizWalkerError::NullPointer { .. }) => Ok(None),
+                Ok(player) if player == current_player => Ok(None),
+                result => result.map(Some)
+            }
+        }, ZONE_POLL_INTERVAL);
+
+        tokio::time::timeout(timeout, changed).await
+            .map_err(|_| WizWalkerError::ZoneChangeTimeout { timeout })?
+    }
+}
+
+impl HandledClient for AsyncClient {
+    fn attach(process_id: u32) -> Result<Self> {
+        Client::attach(process_id).map(Self::new)
+    }
+
+    fn is_running(&self) -> Result<bool> {
+        self.client.lock()?.is_running()
+    }
+
+    fn activate_hooks(&mut self) -> Result<()> {
+        self.client.lock()?.activate_hooks()
+    }
+
+    fn deactivate_hooks(&mut self) -> Result<()> {
+        self.client.lock()?.deactivate_hooks()
+    }
+}
+
+
+/// A `ClientHandler` whose enumeration and attaching run on the blocking pool.
+#[derive(Clone)]
+pub struct AsyncClientHandler {
+    handler: Arc<Mutex<ClientHandler<ToolhelpEnumerator, AsyncClient>>>,
+}
+
+impl Default for AsyncClientHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsyncClientHandler {
+    pub fn new() -> Self {
+        Self {
+            handler: Arc::new(Mutex::new(ClientHandler::with_enumerator(ToolhelpEnumerator))),
+        }
+    }
+
+    async fn run<T, F>(&self, action: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut ClientHandler<ToolhelpEnumerator, AsyncClient>) -> Result<T> + Send + 'static,
+    {
+        let handler = self.handler.clone();
+        run_blocking(move || action(&mut *handler.lock()?)).await
+    }
+
+    /// Attaches to every game process that is not handled yet and returns the new clients.
+    pub async fn get_new_clients(&self) -> Result<Vec<AsyncClient>> {
+        self.run(|handler| {
+            let new_clients = handler.get_new_clients()?;
+
+            Ok(new_clients.into_iter()
+                .filter_map(|process_id| handler.client(process_id).cloned())
+                .collect())
+        }).await
+    }
+
+    /// Drops exited clients, then attaches to new ones.
+    pub async fn refresh(&self) -> Result<ClientChanges> {
+        self.run(|handler| handler.refresh()).await
+    }
+
+    /// Every handled client, ordered by process id.
+    pub async fn clients(&self) -> Result<Vec<AsyncClient>> {
+        self.run(|handler| Ok(handler.clients().map(|(_, client)| client.clone()).collect())).await
+    }
+
+    pub async fn activate_all_client_hooks(&self) -> Result<()> {
+        self.run(|handler| handler.activate_all_client_hooks()).await
+    }
+
+    pub async fn deactivate_all_client_hooks(&self) -> Result<()> {
+        self.run(|handler| handler.deactivate_all_client_hooks()).await
+    }
+
+    pub async fn close(&self) -> Result<()> {
+        self.run(|handler| handler.close()).await
+    }
+}
+
+
+/// Reads `file_name` out of the game data wad `wad_name`.
+pub async fn read_wad_file(wad_name: &str, file_name: &str) -> Result<Vec<u8>> {
+    let wad_name = wad_name.to_string();
+    let file_name = file_name.to_string();
+
+    run_blocking(move || {
+        let mut wad = Wad::new(&PathBuf::from(&wad_name));
+        wad.from_game_data(&wad_name)?;
+        wad.open()?;
+
+        wad.get_file(&file_name)
+    }).await
+}
+
+/// Names of every file in the game data wad `wad_name`.
+pub async fn wad_file_names(wad_name: &str) -> Result<Vec<String>> {
+    let wad_name = wad_name.to_string();
+
+    run_blocking(move || {
+        let mut wad = Wad::new(&PathBuf::from(&wad_name));
+        wad.from_game_data(&wad_name)?;
+
+        wad.names()
+    }).await
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn poll_until_returns_the_first_value() {
+        let polls = AtomicUsize::new(0);
+
+        let value = poll_until(|| async {
+            match polls.fetch_add(1, Ordering::SeqCst) {
+                0..=2 => Ok(None),
+                count => Ok(Some(count))
+            }
+        }, Duration::from_millis(1)).await.unwrap();
+
+        assert_eq!(value, 3);
+    }
+
+    #[tokio::test]
+    async fn poll_until_stops_on_errors() {
+        let result: Result<()> = poll_until(|| async { Err(WizWalkerError::ClientClosed) }, Duration::from_millis(1)).await;
+        assert!(matches!(result, Err(WizWalkerError::ClientClosed)));
+    }
+
+    #[tokio::test]
+    async fn blocking_work_runs_off_the_runtime() {
+        let caller = std::thread::current().id();
+        let worker = run_blocking(|| Ok(std::thread::current().id())).await.unwrap();
+
+        assert_ne!(worker, caller);
+        assert_eq!(run_blocking(|| Ok(21 * 2)).await.unwrap(), 42);
+    }
+}
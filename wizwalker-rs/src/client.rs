@@ -1,8 +1,298 @@
-use winapi::um::winnt::HANDLE;
-// use crate::memory::hooks::WizWalkerMemoryHook;
+use winapi::shared::minwindef::FALSE;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::OpenProcess;
+use winapi::um::winnt::{HANDLE, PROCESS_ALL_ACCESS};
+
+use std::ptr::null_mut;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{Result, WizWalkerError};
+use crate::file_readers::cache_handler::CacheHandler;
+use crate::memory::handler::HookHandler;
+use crate::memory::hooks::{
+    ClientHook, DuelHook, MovementTeleportHook, PlayerHook, QuestHook, RenderContextHook, RootWindowHook,
+};
+use crate::memory::memory_objects::actor_body::ActorBody;
+use crate::memory::memory_objects::client_object::{ClientObject, CurrentClientObject, GAME_STATS_OFFSET};
+use crate::memory::memory_objects::duel::Duel;
+use crate::memory::memory_objects::game_stats::GameStats;
+use crate::memory::memory_objects::render_context::RenderContext;
+use crate::memory::memory_objects::window::Window;
+use crate::memory::memory_object::MemoryObject;
+use crate::memory::memory_reader::MemoryReader;
+use crate::memory::registry::{unregister_process_emergency_restores, ActivatableHook, HookInfo, HookRegistry};
+use crate::utils::{check_if_process_running, get_pid_window, Rectangle, XYZ};
 
 pub trait WizWalkerClient {
     fn window_handle(&self) -> HANDLE;
-    fn hook_handler(&mut self) -> &Self;
-    // fn cache_handler 
-}
\ No newline at end of file
+
+    /// The code cave allocator shared by this client's hooks.
+    fn hook_handler(&self) -> Arc<Mutex<HookHandler>>;
+
+    /// Address of the local player's actor body, captured by the player hook.
+    fn player_base(&mut self) -> Result<usize>;
+
+    /// The duel the client is in, captured by the duel hook.
+    fn duel(&mut self) -> Result<Duel>;
+
+    /// Position of the active quest, captured by the quest hook.
+    fn quest_position(&mut self) -> Result<XYZ>;
+
+    /// The local player's client object, captured by the client hook.
+    fn client_object(&mut self) -> Result<ClientObject>;
+
+    /// The local player's actor body, captured by the player hook.
+    fn body(&mut self) -> Result<ActorBody>;
+
+    /// The local wizard's stats, through the client object.
+    fn stats(&mut self) -> Result<GameStats>;
+
+    /// Moves the player to `position` on its next movement update.
+    fn teleport(&mut self, position: XYZ) -> Result<()>;
+
+    /// Root of the UI window tree, captured by the root window hook.
+    fn root_window(&mut self) -> Result<Window>;
+
+    /// The UI scale, read from the render context captured by the render context hook.
+    fn ui_scale(&mut self) -> Result<f32>;
+
+    /// `window`'s rectangle in client coordinates.
+    fn window_to_client(&mut self, window: Window) -> Result<Rectangle>;
+
+    /// Size of the client's UI in pixels, from the root window's rectangle.
+    fn screen_size(&mut self) -> Result<(i32, i32)>;
+    // fn cache_handler
+}
+
+
+/// One attached game client: its process, memory, hooks and game files.
+///
+/// Every hook is registered on attach but none is installed until
+/// `activate_hooks` or `activate_hook` is called. Detaching (or dropping the
+/// client) removes every hook and closes the process handle.
+pub struct Client {
+    process_id: u32,
+    process: HANDLE,
+    window_handle: HANDLE,
+    memory: MemoryReader,
+    hook_handler: Arc<Mutex<HookHandler>>,
+    hooks: HookRegistry,
+    cache_handler: Option<CacheHandler>,
+}
+
+// The handles are only passed to thread safe Win32 calls, and the hooks only
+// hold handles and a `Mutex`-guarded allocator.
+unsafe impl Send for Client {}
+
+impl Client {
+    pub fn attach(process_id: u32) -> Result<Self> {
+        let process = unsafe { OpenProcess(PROCESS_ALL_ACCESS, FALSE, process_id) };
+        if process.is_null() {
+            return Err(WizWalkerError::AttachFailed { pid: process_id })
+        }
+
+        let hook_handler = Arc::new(Mutex::new(HookHandler::new(process)));
+
+        let mut hooks = HookRegistry::new();
+        hooks.register(Box::new(PlayerHook::new(process, hook_handler.clone())))?;
+        hooks.register(Box::new(ClientHook::new(process, hook_handler.clone())))?;
+        hooks.register(Box::new(DuelHook::new(process, hook_handler.clone())))?;
+        hooks.register(Box::new(QuestHook::new(process, hook_handler.clone())))?;
+        hooks.register(Box::new(MovementTeleportHook::new(process, hook_handler.clone())))?;
+        hooks.register(Box::new(RootWindowHook::new(process, hook_handler.clone())))?;
+        hooks.register(Box::new(RenderContextHook::new(process, hook_handler.clone())))?;
+
+        let window_handle = get_pid_window(process_id)
+            .map_or(null_mut(), |window| window as HANDLE);
+
+        Ok(Self {
+            process_id,
+            process,
+            window_handle,
+            memory: MemoryReader::new(process),
+            hook_handler,
+            hooks,
+            cache_handler: None,
+        })
+    }
+
+    /// Removes every hook and closes the process handle.
+    ///
+    /// If a hook cannot be removed the handle stays open, so its emergency
+    /// restore stays valid and `detach` can be retried.
+    pub fn detach(&mut self) -> Result<()> {
+        if self.process.is_null() {
+            return Ok(())
+        }
+
+        if self.is_running()? {
+            self.hooks.deactivate_all()?;
+        } else {
+            // Nothing left to restore in a process that is gone.
+            self.hooks.forget_active();
+            unregister_process_emergency_restores(self.process);
+        }
+
+        unsafe { CloseHandle(self.process) };
+        self.process = null_mut();
+
+        Ok(())
+    }
+
+    pub fn is_attached(&self) -> bool {
+        !self.process.is_null()
+    }
+
+    pub fn is_running(&self) -> Result<bool> {
+        if self.process.is_null() {
+            return Ok(false)
+        }
+
+        check_if_process_running(self.process)
+    }
+
+    pub fn process_id(&self) -> u32 {
+        self.process_id
+    }
+
+    pub fn process(&self) -> HANDLE {
+        self.process
+    }
+
+    /// Looks for the game window again, e.g. once a freshly launched client opened it.
+    pub fn refresh_window_handle(&mut self) -> HANDLE {
+        self.window_handle = get_pid_window(self.process_id)
+            .map_or(null_mut(), |window| window as HANDLE);
+
+        self.window_handle
+    }
+
+    pub fn memory(&mut self) -> &mut MemoryReader {
+        &mut self.memory
+    }
+
+    pub fn hooks(&mut self) -> &mut HookRegistry {
+        &mut self.hooks
+    }
+
+    /// The game file cache, loaded on first use.
+    pub fn cache_handler(&mut self) -> &mut CacheHandler {
+        self.cache_handler.get_or_insert_with(CacheHandler::new)
+    }
+
+    /// Installs every registered hook.
+    pub fn activate_hooks(&mut self) -> Result<()> {
+        let names: Vec<&'static str> = self.hooks.status()
+            .into_iter()
+            .map(|status| status.name)
+            .collect();
+
+        for name in names {
+            self.hooks.activate(name)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn activate_hook(&mut self, name: &str) -> Result<()> {
+        self.hooks.activate(name)
+    }
+
+    /// Removes every active hook.
+    pub fn deactivate_hooks(&mut self) -> Result<()> {
+        self.hooks.deactivate_all()
+    }
+
+    pub fn deactivate_hook(&mut self, name: &str) -> Result<()> {
+        self.hooks.deactivate(name)
+    }
+
+    /// Follows the player's client object for as long as the client hook stays active.
+    pub fn current_client_object(&mut self) -> Result<CurrentClientObject> {
+        match self.hook::<ClientHook>()?.current_client_addr() {
+            0 => Err(WizWalkerError::HookNotActive { name: <ClientHook>::NAME.to_string() }),
+            export_address => Ok(CurrentClientObject::new(export_address))
+        }
+    }
+
+    pub fn render_context(&mut self) -> Result<RenderContext> {
+        self.hook::<RenderContextHook>()?.render_context()
+    }
+
+    fn hook<H: ActivatableHook + HookInfo>(&mut self) -> Result<&mut H> {
+        if self.process.is_null() {
+            return Err(WizWalkerError::ClientClosed)
+        }
+
+        self.hooks.get::<H>(H::NAME)
+            .ok_or(WizWalkerError::HookNotRegistered { name: H::NAME.to_string() })
+    }
+}
+
+impl WizWalkerClient for Client {
+    fn window_handle(&self) -> HANDLE {
+        self.window_handle
+    }
+
+    fn hook_handler(&self) -> Arc<Mutex<HookHandler>> {
+        self.hook_handler.clone()
+    }
+
+    fn player_base(&mut self) -> Result<usize> {
+        self.hook::<PlayerHook>()?.player_base()
+    }
+
+    fn duel(&mut self) -> Result<Duel> {
+        self.hook::<DuelHook>()?.current_duel()
+    }
+
+    fn quest_position(&mut self) -> Result<XYZ> {
+        self.hook::<QuestHook>()?.quest_position()
+    }
+
+    fn client_object(&mut self) -> Result<ClientObject> {
+        Ok(ClientObject::new(self.hook::<ClientHook>()?.client_base()?))
+    }
+
+    fn body(&mut self) -> Result<ActorBody> {
+        self.hook::<PlayerHook>()?.body()
+    }
+
+    fn stats(&mut self) -> Result<GameStats> {
+        let client_object = self.client_object()?;
+        client_object.game_stats(&mut self.memory)?
+            .ok_or(WizWalkerError::NullPointer { address: client_object.base_address() + GAME_STATS_OFFSET })
+    }
+
+    fn teleport(&mut self, position: XYZ) -> Result<()> {
+        let body = self.player_base()?;
+        self.hook::<MovementTeleportHook>()?.teleport(body, position)
+    }
+
+    fn root_window(&mut self) -> Result<Window> {
+        self.hook::<RootWindowHook>()?.root_window()
+    }
+
+    fn ui_scale(&mut self) -> Result<f32> {
+        let render_context = self.render_context()?;
+        render_context.ui_scale(&mut self.memory)
+    }
+
+    fn window_to_client(&mut self, window: Window) -> Result<Rectangle> {
+        let ui_scale = self.ui_scale()?;
+        window.scale_to_client(&mut self.memory, ui_scale)
+    }
+
+    fn screen_size(&mut self) -> Result<(i32, i32)> {
+        let root_rectangle = self.root_window()?.window_rectangle(&mut self.memory)?;
+        Ok((root_rectangle.width(), root_rectangle.height()))
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if let Err(error) = self.detach() {
+            log::error!("Failed to detach from client {}: {error}", self.process_id);
+        }
+    }
+}
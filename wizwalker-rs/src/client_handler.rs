@@ -0,0 +1,334 @@
+use winapi::um::handleapi::{CloseHandle, INVALID_HANDLE_VALUE};
+use winapi::um::tlhelp32::{
+    CreateToolhelp32Snapshot, Process32First, Process32Next, PROCESSENTRY32, TH32CS_SNAPPROCESS,
+};
+
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use std::mem::{size_of, zeroed};
+
+use crate::client::Client;
+use crate::error::{Result, WizWalkerError};
+use crate::memory::signatures::CLIENT_MODULE;
+
+
+/// Lists running processes by executable name.
+pub trait ProcessEnumerator {
+    /// Ids of every running process started from `executable_name`, compared case-insensitively.
+    fn process_ids(&mut self, executable_name: &str) -> Result<Vec<u32>>;
+}
+
+
+/// Enumerates processes through a Toolhelp snapshot.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ToolhelpEnumerator;
+
+impl ProcessEnumerator for ToolhelpEnumerator {
+    fn process_ids(&mut self, executable_name: &str) -> Result<Vec<u32>> {
+        let mut process_ids = Vec::new();
+
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return Err(std::io::Error::last_os_error().into())
+            }
+
+            let mut process_entry: PROCESSENTRY32 = zeroed();
+            process_entry.dwSize = size_of::<PROCESSENTRY32>() as u32;
+
+            let mut has_entry = Process32First(snapshot, &mut process_entry) != 0;
+            while has_entry {
+                let exe_name = CStr::from_ptr(process_entry.szExeFile.as_ptr());
+                if let Ok(name) = exe_name.to_str() {
+                    if name.eq_ignore_ascii_case(executable_name) {
+                        process_ids.push(process_entry.th32ProcessID);
+                    }
+                }
+
+                has_entry = Process32Next(snapshot, &mut process_entry) != 0;
+            }
+
+            CloseHandle(snapshot);
+        }
+
+        Ok(process_ids)
+    }
+}
+
+
+/// What `ClientHandler` needs from a client.
+pub trait HandledClient: Sized {
+    fn attach(process_id: u32) -> Result<Self>;
+
+    fn is_running(&self) -> Result<bool>;
+
+    fn activate_hooks(&mut self) -> Result<()>;
+
+    fn deactivate_hooks(&mut self) -> Result<()>;
+}
+
+impl HandledClient for Client {
+    fn attach(process_id: u32) -> Result<Self> {
+        Client::attach(process_id)
+    }
+
+    fn is_running(&self) -> Result<bool> {
+        Client::is_running(self)
+    }
+
+    fn activate_hooks(&mut self) -> Result<()> {
+        Client::activate_hooks(self)
+    }
+
+    fn deactivate_hooks(&mut self) -> Result<()> {
+        Client::deactivate_hooks(self)
+    }
+}
+
+
+/// Process ids that appeared or went away during a `ClientHandler::refresh`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClientChanges {
+    pub new: Vec<u32>,
+    pub exited: Vec<u32>,
+}
+
+
+/// Keeps one attached client per running game process.
+///
+/// Clients are cached by process id and ordered by it, so a given client
+/// keeps its place for as long as its process lives.
+pub struct ClientHandler<E: ProcessEnumerator = ToolhelpEnumerator, C: HandledClient = Client> {
+    enumerator: E,
+    clients: BTreeMap<u32, C>,
+}
+
+impl ClientHandler {
+    pub fn new() -> Self {
+        Self::with_enumerator(ToolhelpEnumerator)
+    }
+}
+
+impl Default for ClientHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: ProcessEnumerator, C: HandledClient> ClientHandler<E, C> {
+    pub fn with_enumerator(enumerator: E) -> Self {
+        Self {
+            enumerator,
+            clients: BTreeMap::new(),
+        }
+    }
+
+    /// Attaches to every game process that is not handled yet and returns their ids.
+    ///
+    /// Processes that cannot be opened are skipped, and retried on the next call.
+    pub fn get_new_clients(&mut self) -> Result<Vec<u32>> {
+        let mut new_clients = Vec::new();
+
+        for process_id in self.enumerator.process_ids(CLIENT_MODULE)? {
+            if self.clients.contains_key(&process_id) {
+                continue
+            }
+
+            match C::attach(process_id) {
+                Ok(client) => {
+                    self.clients.insert(process_id, client);
+                    new_clients.push(process_id);
+                }
+                Err(error) => log::warn!("Failed to attach to client {process_id}: {error}")
+            }
+        }
+
+        Ok(new_clients)
+    }
+
+    /// Drops every client whose process exited and returns their ids.
+    pub fn remove_dead_clients(&mut self) -> Vec<u32> {
+        let dead_clients: Vec<u32> = self.clients.iter()
+            .filter(|(_, client)| !matches!(client.is_running(), Ok(true)))
+            .map(|(process_id, _)| *process_id)
+            .collect();
+
+        for process_id in &dead_clients {
+            self.clients.remove(process_id);
+        }
+
+        dead_clients
+    }
+
+    /// Drops exited clients, then attaches to new ones.
+    pub fn refresh(&mut self) -> Result<ClientChanges> {
+        let exited = self.remove_dead_clients();
+        let new = self.get_new_clients()?;
+
+        Ok(ClientChanges { new, exited })
+    }
+
+    pub fn client(&mut self, process_id: u32) -> Option<&mut C> {
+        self.clients.get_mut(&process_id)
+    }
+
+    /// Every handled client, ordered by process id.
+    pub fn clients(&mut self) -> impl Iterator<Item = (u32, &mut C)> {
+        self.clients.iter_mut().map(|(process_id, client)| (*process_id, client))
+    }
+
+    pub fn process_ids(&self) -> Vec<u32> {
+        self.clients.keys().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Activates the hooks of every client, continuing past clients that fail.
+    pub fn activate_all_client_hooks(&mut self) -> Result<()> {
+        self.for_each_client(C::activate_hooks)
+    }
+
+    /// Deactivates the hooks of every client, continuing past clients that fail.
+    pub fn deactivate_all_client_hooks(&mut self) -> Result<()> {
+        self.for_each_client(C::deactivate_hooks)
+    }
+
+    /// Deactivates every client's hooks and drops them all.
+    pub fn close(&mut self) -> Result<()> {
+        let result = self.deactivate_all_client_hooks();
+        self.clients.clear();
+
+        result
+    }
+
+    fn for_each_client(&mut self, mut action: impl FnMut(&mut C) -> Result<()>) -> Result<()> {
+        let errors: Vec<String> = self.clients.iter_mut()
+            .filter_map(|(process_id, client)| action(client).err().map(|error| format!("{process_id}: {error}")))
+            .collect();
+
+        if !errors.is_empty() {
+            return Err(WizWalkerError::ClientsFailed { count: errors.len(), details: errors.join("; ") })
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+
+    // Each test runs on its own thread, so each gets its own set of "running" processes.
+    thread_local! {
+        static RUNNING: RefCell<HashSet<u32>> = RefCell::new(HashSet::new());
+    }
+
+    fn set_running(process_ids: &[u32]) {
+        RUNNING.with(|running| *running.borrow_mut() = process_ids.iter().copied().collect());
+    }
+
+    fn is_running(process_id: u32) -> bool {
+        RUNNING.with(|running| running.borrow().contains(&process_id))
+    }
+
+    struct FakeEnumerator;
+
+    impl ProcessEnumerator for FakeEnumerator {
+        fn process_ids(&mut self, executable_name: &str) -> Result<Vec<u32>> {
+            assert_eq!(executable_name, CLIENT_MODULE);
+            Ok(RUNNING.with(|running| running.borrow().iter().copied().collect()))
+        }
+    }
+
+    struct FakeClient {
+        process_id: u32,
+        hooks_active: bool,
+    }
+
+    impl HandledClient for FakeClient {
+        fn attach(process_id: u32) -> Result<Self> {
+            Ok(Self { process_id, hooks_active: false })
+        }
+
+        fn is_running(&self) -> Result<bool> {
+            Ok(is_running(self.process_id))
+        }
+
+        fn activate_hooks(&mut self) -> Result<()> {
+            if !self.is_running()? {
+                return Err(WizWalkerError::ClientClosed)
+            }
+
+            self.hooks_active = true;
+            Ok(())
+        }
+
+        fn deactivate_hooks(&mut self) -> Result<()> {
+            self.hooks_active = false;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn refresh_tracks_launched_and_exited_clients() {
+        let mut handler: ClientHandler<FakeEnumerator, FakeClient> = ClientHandler::with_enumerator(FakeEnumerator);
+
+        set_running(&[30, 10, 20]);
+        let changes = handler.refresh().unwrap();
+        assert_eq!(changes.exited, Vec::<u32>::new());
+        let mut new = changes.new;
+        new.sort();
+        assert_eq!(new, vec![10, 20, 30]);
+        assert_eq!(handler.process_ids(), vec![10, 20, 30]);
+
+        set_running(&[10, 30, 40]);
+        let changes = handler.refresh().unwrap();
+        assert_eq!(changes, ClientChanges { new: vec![40], exited: vec![20] });
+        assert_eq!(handler.process_ids(), vec![10, 30, 40]);
+
+        assert_eq!(handler.refresh().unwrap(), ClientChanges::default());
+    }
+
+    #[test]
+    fn hooks_are_toggled_on_every_client() {
+        let mut handler: ClientHandler<FakeEnumerator, FakeClient> = ClientHandler::with_enumerator(FakeEnumerator);
+
+        set_running(&[1, 2]);
+        handler.get_new_clients().unwrap();
+        handler.activate_all_client_hooks().unwrap();
+        assert!(handler.clients().all(|(_, client)| client.hooks_active));
+
+        handler.deactivate_all_client_hooks().unwrap();
+        assert!(handler.clients().all(|(_, client)| !client.hooks_active));
+    }
+
+    #[test]
+    fn one_failing_client_does_not_stop_the_others() {
+        let mut handler: ClientHandler<FakeEnumerator, FakeClient> = ClientHandler::with_enumerator(FakeEnumerator);
+
+        set_running(&[1, 2, 3]);
+        handler.get_new_clients().unwrap();
+        set_running(&[1, 3]);
+
+        match handler.activate_all_client_hooks() {
+            Err(WizWalkerError::ClientsFailed { count: 1, .. }) => {}
+            other => panic!("expected one failed client, got {other:?}")
+        }
+
+        assert!(handler.client(1).unwrap().hooks_active);
+        assert!(!handler.client(2).unwrap().hooks_active);
+        assert!(handler.client(3).unwrap().hooks_active);
+
+        handler.close().unwrap();
+        assert!(handler.is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+
+pub type Result<T, E = WizWalkerError> = std::result::Result<T, E>;
+
+
+/// Every failure wizwalker-rs can report.
+///
+/// Variants carry the address, pattern or file involved so automation can
+/// react to the cause, e.g. reattaching on `ClientClosed` instead of retrying
+/// a read that can never succeed.
+#[derive(Debug, Error)]
+pub enum WizWalkerError {
+    // Process
+    #[error("Client must be running to perform this action.")]
+    ClientClosed,
+
+    #[error("Unknown process exit code: {0}")]
+    UnknownExitCode(u32),
+
+    #[error("Unable to open process {pid}.")]
+    AttachFailed { pid: u32 },
+
+    #[error("Process {pid} has no game window yet.")]
+    WindowNotFound { pid: u32 },
+
+    #[error("{count} client(s) failed: {details}")]
+    ClientsFailed { count: usize, details: String },
+
+    // Memory access
+    #[error("Address \"{address:#x}\" out of bounds")]
+    AddressOutOfBounds { address: usize },
+
+    #[error("Unable to read {size} bytes of memory at address \"{address:#x}\".")]
+    ReadFailed { address: usize, size: usize },
+
+    #[error("Unable to write {size} bytes of memory at address \"{address:#x}\".")]
+    WriteFailed { address: usize, size: usize },
+
+    #[error("Verification of {size} bytes written at address \"{address:#x}\" failed.")]
+    WriteVerificationFailed { address: usize, size: usize },
+
+    #[error("Unable to change protection of memory at address \"{address:#x}\" to {protection:#x}.")]
+    ProtectFailed { address: usize, protection: u32 },
+
+    #[error("Unable to flush instruction cache at address \"{address:#x}\".")]
+    FlushFailed { address: usize },
+
+    #[error("Failed to query memory region at address \"{address:#x}\".")]
+    QueryFailed { address: usize },
+
+    #[error("Failed to allocate {size} bytes of memory.")]
+    AllocationFailed { size: usize },
+
+    #[error("Failed to free memory at address \"{address:#x}\".")]
+    FreeFailed { address: usize },
+
+    #[error("Failed to create a thread at address \"{address:#x}\".")]
+    ThreadCreationFailed { address: usize },
+
+    #[error("Null pointer read at address \"{address:#x}\".")]
+    NullPointer { address: usize },
+
+    /// `slot` is where the null was read, `offset` the chain offset that led there.
+    /// Depth 0 means the chain's base itself was null.
+    #[error("Null pointer read from \"{slot:#x}\" at depth {depth} of a pointer chain (offset {offset:#x}).")]
+    NullPointerInChain { slot: usize, depth: usize, offset: usize },
+
+    #[error("Dereferenced a null RemotePtr<{type_name}>.")]
+    NullDereference { type_name: &'static str },
+
+    #[error("Invalid data at address \"{address:#x}\": {reason}")]
+    InvalidData { address: usize, reason: String },
+
+    #[error("{value} is not a valid {name}.")]
+    InvalidEnumValue { name: &'static str, value: i64 },
+
+    #[error("Address \"{address:#x}\" ({size} bytes) was not captured.")]
+    NotCaptured { address: usize, size: usize },
+
+    #[error("Memory kept changing across {attempts} snapshot attempts.")]
+    SnapshotUnstable { attempts: usize },
+
+    #[error("Cannot write to address \"{address:#x}\" through a read-only view.")]
+    ReadOnly { address: usize },
+
+    #[error("Failed to roll back {count} patch(es): {details}")]
+    RollbackFailed { count: usize, details: String },
+
+    // Scanning and symbols
+    #[error("Pattern \"{pattern}\" failed. You most likely need to restart the client.")]
+    PatternNotFound { pattern: String },
+
+    #[error("Got {count} results for pattern \"{pattern}\".")]
+    PatternNotUnique { pattern: String, count: usize },
+
+    #[error("\"{module}\" module not found.")]
+    ModuleNotFound { module: String },
+
+    #[error("No symbol named \"{symbol}\" in module \"{module}\".")]
+    SymbolNotFound { module: String, symbol: String },
+
+    // Hooks
+    #[error("Jump from \"{from:#x}\" to \"{to:#x}\" is out of rel32 range.")]
+    JumpOutOfRange { from: usize, to: usize },
+
+    #[error("Invalid or truncated instruction at address \"{address:#x}\".")]
+    InvalidInstruction { address: usize },
+
+    #[error("Refusing to hook \"{address:#x}\": {reason}.")]
+    UnsafeHookSite { address: usize, reason: String },
+
+    #[error("Hook \"{name}\" is not active.")]
+    HookNotActive { name: String },
+
+    #[error("Hook export at \"{address:#x}\" was still null after {timeout:?}.")]
+    HookTimeout { address: usize, timeout: std::time::Duration },
+
+    #[error("The player did not change zones within {timeout:?}.")]
+    ZoneChangeTimeout { timeout: std::time::Duration },
+
+    #[error("No hook named \"{name}\" is registered.")]
+    HookNotRegistered { name: String },
+
+    #[error("A hook named \"{name}\" is already registered.")]
+    HookAlreadyRegistered { name: String },
+
+    #[error("Hook \"{name}\" depends on itself through its dependencies.")]
+    HookDependencyCycle { name: String },
+
+    #[error("Hook bytecode of {size} bytes does not fit its {allocated} byte allocation.")]
+    HookTooLarge { size: usize, allocated: usize },
+
+    // Type dump
+    #[error("No type named \"{name}\" in the type dump.")]
+    TypeNotFound { name: String },
+
+    #[error("Type \"{class}\" has no property \"{property}\" in the type dump.")]
+    PropertyNotFound { class: String, property: String },
+
+    // Game files
+    #[error("Unable to find Wizard101 installation in default or Steam install directories.")]
+    InstallNotFound,
+
+    #[error("Unable to find game data wad at path \"{path}\" on system.")]
+    WadNotFound { path: PathBuf },
+
+    #[error("Call open() on wad \"{wad}\" before using this method.")]
+    WadNotOpen { wad: String },
+
+    #[error("File \"{file}\" not found in wad \"{wad}\".")]
+    WadEntryNotFound { wad: String, file: String },
+
+    #[error("Could not get cache folder.")]
+    CacheFolderNotFound,
+
+    #[error("The {cache} cache has not been loaded yet.")]
+    CacheNotLoaded { cache: &'static str },
+
+    #[error("Unable to parse \"{file}\": {reason}")]
+    ParseFailed { file: String, reason: String },
+
+    #[error("No lang entry for langcode \"{langcode}\".")]
+    LangcodeNotFound { langcode: String },
+
+    #[error("A lock was poisoned: {0}")]
+    LockPoisoned(String),
+
+    #[error("A background task failed: {0}")]
+    TaskFailed(String),
+
+    // Wrapped library errors
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Pe(#[from] pelite::Error),
+
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+
+    #[error(transparent)]
+    Assembler(#[from] iced_x86::IcedError),
+
+    #[error(transparent)]
+    Utf8(#[from] std::string::FromUtf8Error),
+
+    #[error(transparent)]
+    Utf8Str(#[from] std::str::Utf8Error),
+
+    #[error(transparent)]
+    IntConversion(#[from] std::num::TryFromIntError),
+
+    #[error(transparent)]
+    CtrlC(#[from] ctrlc::Error),
+}
+
+impl WizWalkerError {
+    /// Whether the error means the client is gone and must be reattached.
+    pub fn is_client_closed(&self) -> bool {
+        matches!(self, WizWalkerError::ClientClosed | WizWalkerError::UnknownExitCode(_))
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for WizWalkerError {
+    fn from(error: std::sync::PoisonError<T>) -> Self {
+        WizWalkerError::LockPoisoned(error.to_string())
+    }
+}
@@ -6,7 +6,7 @@ use std::io::{Read, Write};
 use std::path::PathBuf;
 // use serde_json::Value;
 // use serde_json::Error as SerdeError;
-use anyhow::{Result, anyhow};
+use crate::error::{Result, WizWalkerError};
 use serde_json::Value;
 
 use crate::utils::{get_cache_folder, get_wiz_install, parse_template_id_file};
@@ -55,7 +55,7 @@ impl CacheHandler {
         return match get_cache_folder() {
             Some(f) => Ok(f),
             None => {
-                Err(anyhow!("Could not get cache folder."))
+                Err(WizWalkerError::CacheFolderNotFound)
             }
         }
     }
@@ -118,7 +118,7 @@ impl CacheHandler {
 
         let wad_cache = match &self.wad_cache {
             Some(a) => a,
-            None => return Err(anyhow!("Wad Cache does not exist. This should never happen."))
+            None => return Err(WizWalkerError::CacheNotLoaded { cache: "wad" })
         };
 
         let template_file = &self.check_updated(wad_cache.clone(), root_wad.clone(), vec!["TemplateManifest.xml"])?;
@@ -163,19 +163,19 @@ impl CacheHandler {
         let decoded_str = match UTF_16LE.decode(&file_data, DecoderTrap::Strict) {
             Ok(dec) => dec,
             Err(e) => {
-                return Err(anyhow!("Parsing lang file failed with the following error: \"{e}\""))
+                return Err(WizWalkerError::ParseFailed { file: "lang file".to_string(), reason: e.to_string() })
             }
         };
 
         let file_lines: Vec<&str> = decoded_str.split("\r\n").collect();
 
         // Get the header and the rest of the lines
-        let header = file_lines.get(0).ok_or(anyhow!("Header line missing"))?;
+        let header = file_lines.get(0).ok_or(WizWalkerError::ParseFailed { file: "lang file".to_string(), reason: "Header line missing".to_string() })?;
         let lines = &file_lines[1..];
 
         // Extract language name from the header
         let parts: Vec<&str> = header.split(':').collect();
-        let lang_name = parts.get(1).ok_or(anyhow!("Language name missing"))?.trim();
+        let lang_name = parts.get(1).ok_or(WizWalkerError::ParseFailed { file: "lang file".to_string(), reason: "Language name missing".to_string() })?.trim();
 
         // Create the language mapping
         let mut lang_mapping = HashMap::new();
@@ -215,7 +215,7 @@ impl CacheHandler {
     fn cache_lang_file(&self, root_wad: Wad, lang_file: &str) -> Result<()> {
         let lang_files = vec![lang_file];
 
-        let wad_cache = self.wad_cache.clone().ok_or(anyhow!("No wad cache"))?;
+        let wad_cache = self.wad_cache.clone().ok_or(WizWalkerError::CacheNotLoaded { cache: "wad" })?;
         if self.check_updated(wad_cache, root_wad.clone(), lang_files)?.is_empty() {
             return Ok(());
         }
@@ -240,7 +240,7 @@ impl CacheHandler {
         let lang_file_names = self.get_all_lang_file_names(root_wad.clone())?;
 
         let mut parsed_lang_map: LangcodeMap = LangcodeMap::new();
-        let wad_cache = self.wad_cache.clone().ok_or(anyhow!("Could not get wad cache."))?;
+        let wad_cache = self.wad_cache.clone().ok_or(WizWalkerError::CacheNotLoaded { cache: "wad" })?;
 
         for file_name in lang_file_names {
             let files: Vec<&str>  = vec![&file_name];
@@ -285,13 +285,13 @@ impl CacheHandler {
             let wad_cache_data: Value = serde_json::from_slice(data.as_slice())?;
 
             let wad_cache_map = wad_cache_data.as_object().ok_or(
-                anyhow!("Unable to read wad cache for wad_cache.data")
+                WizWalkerError::ParseFailed { file: "wad_cache.data".to_string(), reason: "Unable to read wad cache".to_string() }
             )?;
 
             for (k, v) in wad_cache_map.iter() {
                 let i = match v.as_object() {
                     Some(i1) => i1,
-                    None => return Err(anyhow!("Unable to read wad cache for wad_cache.data"))
+                    None => return Err(WizWalkerError::ParseFailed { file: "wad_cache.data".to_string(), reason: "Unable to read wad cache".to_string() })
                 };
                 
                 wad_cache.insert(k.to_owned(), HashMap::new());
@@ -331,13 +331,10 @@ impl CacheHandler {
 
         let file = match &self.root_wad.file {
             Some(f) => f,
-            None => return Err(anyhow!("No file found."))
+            None => return Err(WizWalkerError::WadNotOpen { wad: self.root_wad.name.clone() })
         };
 
-        let mut file_lock = match file.lock() {
-            Ok(l) => l,
-            Err(e) => return Err(anyhow!("Acquiring wad file lock failed with the following error: \"{}\"", e))
-        };
+        let mut file_lock = file.lock()?;
 
         file_lock.write(json_data_vec.as_slice())?;
 
@@ -366,7 +363,8 @@ impl CacheHandler {
     }
 
 
-    fn get_template_name(&mut self, template_id: i32) -> Result<Option<String>> {
+    /// Name of the object template with `template_id`, from the cached template ids.
+    pub fn get_template_name(&mut self, template_id: i32) -> Result<Option<String>> {
         let template_ids = self.get_template_ids()?;
 
         let template_name_opt = template_ids.get(&template_id).cloned();
@@ -374,7 +372,7 @@ impl CacheHandler {
     }
 
     fn get_langcode_name(&self, langcode: &str) -> Result<String> {
-        let split_point = langcode.find("_").ok_or(anyhow!("Could not find \"_\" in langcode \"{langcode}\""))?;
+        let split_point = langcode.find("_").ok_or(WizWalkerError::LangcodeNotFound { langcode: langcode.to_string() })?;
         let lang_filename = &langcode[..split_point];
         let code = &langcode[split_point + 1..];
 
@@ -390,14 +388,14 @@ impl CacheHandler {
         }
 
         if !cached {
-            return Err(anyhow!("No lang file named {lang_filename}"))
+            return Err(WizWalkerError::WadEntryNotFound { wad: self.root_wad.name.clone(), file: format!("Locale/English/{lang_filename}.lang") })
         }
 
         let langcode_map = self.get_langcode_map()?;
         
-        let lang_file = langcode_map.get(code).ok_or(anyhow!("No lang file named {lang_filename}"))?;
+        let lang_file = langcode_map.get(code).ok_or(WizWalkerError::WadEntryNotFound { wad: self.root_wad.name.clone(), file: format!("Locale/English/{lang_filename}.lang") })?;
 
-        let lang_name = lang_file.get(code).ok_or(anyhow!("No lang name with code {code}"))?;
+        let lang_name = lang_file.get(code).ok_or(WizWalkerError::LangcodeNotFound { langcode: langcode.to_string() })?;
         
         return Ok(lang_name.clone())
     }
@@ -1,7 +1,7 @@
-use std::io::{Read, Seek, Write};
+use std::io::{ErrorKind, Read, Seek, Write};
 use std::path::PathBuf;
 use std::fs::{File, create_dir};
-use anyhow::{Result, anyhow};
+use crate::error::{Result, WizWalkerError};
 use std::sync::{Arc, Mutex};
 use byteorder::{LittleEndian, ReadBytesExt};
 use flate2::read::ZlibDecoder;
@@ -77,7 +77,7 @@ impl Wad {
         new_path.push(new_name);
 
         if !new_path.exists() {
-            return Err(anyhow!("Unable to find game data wad at path \"{}\" on system.", new_path.display()))
+            return Err(WizWalkerError::WadNotFound { path: new_path })
         }
 
         self.file = None;
@@ -113,13 +113,10 @@ impl Wad {
 
         let file = match &self.file {
             Some(f) => f,
-            None => return Err(anyhow!("No file found."))
+            None => return Err(WizWalkerError::WadNotOpen { wad: self.name.clone() })
         };
 
-        let file_lock = match file.lock() {
-            Ok(l) => l,
-            Err(e) => return Err(anyhow!("Acquiring wad file lock failed with the following error: \"{}\"", e))
-        };
+        let file_lock = file.lock()?;
         Ok(file_lock.metadata()?.len())
     }
 
@@ -136,12 +133,9 @@ impl Wad {
     pub fn refresh_journal(&mut self) -> Result<()> {
         if self.refreshed_once { return Ok(()) }
 
-        let file = &self.file.clone().ok_or(anyhow!("Call open() on this wad before using this method."))?;
+        let file = &self.file.clone().ok_or(WizWalkerError::WadNotOpen { wad: self.name.clone() })?;
 
-        let mut file_lock = match file.lock() {
-            Ok(l) => l,
-            Err(e) => return Err(anyhow!("Acquiring wad file lock failed with the following error: \"{}\"", e))
-        };
+        let mut file_lock = file.lock()?;
         
         file_lock.seek(std::io::SeekFrom::Start(5))?;
         let version = file_lock.read_i32::<LittleEndian>()?;
@@ -182,16 +176,11 @@ impl Wad {
             if file.name == name { maybe_target_file = Some(file) }
         }
 
-        let target_file = maybe_target_file.ok_or(anyhow!("File \"{name}\" not found."))?;
-
-        let file = match &self.file {
-            Some(f) => f.lock(),
-            None => return Err(anyhow!("No file found."))
-        };
+        let target_file = maybe_target_file.ok_or(WizWalkerError::WadEntryNotFound { wad: self.name.clone(), file: name.to_string() })?;
 
-        let mut file_lock = match file {
-            Ok(l) => l,
-            Err(e) => return Err(anyhow!("Acquiring wad file lock failed with the following error: \"{}\"", e))
+        let mut file_lock = match &self.file {
+            Some(f) => f.lock()?,
+            None => return Err(WizWalkerError::WadNotOpen { wad: self.name.clone() })
         };
 
         file_lock.seek(std::io::SeekFrom::Start(target_file.size as u64))?;
@@ -213,7 +202,7 @@ impl Wad {
 
 
     pub fn get_file_info(&self, name: &str) -> Result<WadFileInfo> {
-        self.file.clone().ok_or(anyhow!("Call open() on this wad before using this method."))?;
+        self.file.clone().ok_or(WizWalkerError::WadNotOpen { wad: self.name.clone() })?;
 
         let mut maybe_target_file: Option<&WadFileInfo> = None;
         for file in &self.file_list {
@@ -222,7 +211,7 @@ impl Wad {
 
         let target_file = match maybe_target_file {
             Some(t) => t,
-            None => return Err(anyhow!("File \"{name}\" not found."))
+            None => return Err(WizWalkerError::WadEntryNotFound { wad: self.name.clone(), file: name.to_string() })
         };
 
         return Ok(target_file.clone())
@@ -230,8 +219,8 @@ impl Wad {
 
 
     pub fn unarchive(&mut self, path: &PathBuf) -> Result<()> {
-        if !path.exists() { return Err(anyhow!("\"{}\" does not exist.", path.display())) }
-        if !path.is_dir() { return Err(anyhow!("\"{}\" does not a directory.", path.display())) }
+        if !path.exists() { return Err(std::io::Error::new(ErrorKind::NotFound, format!("\"{}\" does not exist.", path.display())).into()) }
+        if !path.is_dir() { return Err(std::io::Error::new(ErrorKind::NotADirectory, format!("\"{}\" is not a directory.", path.display())).into()) }
 
         for file in &self.file_list {
             let dirs: Vec<&str> = file.name.split("/").collect();
@@ -0,0 +1,116 @@
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{SendMessageW, WM_CHAR};
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::client::{Client, WizWalkerClient};
+use crate::error::{Result, WizWalkerError};
+use crate::memory::signatures::CLIENT_MODULE;
+use crate::utils::get_wiz_install;
+
+
+/// Login server the official launcher points the client at.
+pub const LOGIN_SERVER: &str = "login.us.wizard101.com";
+
+pub const LOGIN_PORT: u16 = 12000;
+
+
+/// Everything needed to start one game client, kept apart from spawning so it can be checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub working_directory: PathBuf,
+}
+
+impl LaunchCommand {
+    pub fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).current_dir(&self.working_directory);
+
+        command
+    }
+}
+
+
+/// The command the launcher runs for the install at `install_path`.
+///
+/// The client runs from `Bin` and is told which login server to connect to
+/// with `-L <server> <port>`.
+pub fn build_launch_command(install_path: &Path, login_server: &str, login_port: u16) -> LaunchCommand {
+    let bin_path = install_path.join("Bin");
+
+    LaunchCommand {
+        program: bin_path.join(CLIENT_MODULE),
+        args: vec!["-L".to_string(), login_server.to_string(), login_port.to_string()],
+        working_directory: bin_path,
+    }
+}
+
+
+/// Starts a client from the install found by `get_wiz_install` and attaches to it.
+///
+/// The client has no window or game state yet; refresh its window handle
+/// before logging in, and only activate hooks once it has loaded.
+pub fn launch(install_path: Option<&str>) -> Result<(u32, Client)> {
+    let install_path = get_wiz_install(install_path)?;
+    let command = build_launch_command(&install_path, LOGIN_SERVER, LOGIN_PORT);
+
+    let process_id = command.to_command().spawn()?.id();
+    let client = Client::attach(process_id)?;
+
+    Ok((process_id, client))
+}
+
+
+/// Starts `count` clients side by side, see `launch`.
+///
+/// Starting the client directly skips the launcher's single instance check.
+pub fn launch_instances(count: usize, install_path: Option<&str>) -> Result<Vec<(u32, Client)>> {
+    (0..count).map(|_| launch(install_path)).collect()
+}
+
+
+/// Types `username` and `password` into the login screen of `client`.
+pub fn login(client: &mut Client, username: &str, password: &str) -> Result<()> {
+    let mut window = client.window_handle();
+    if window.is_null() {
+        window = client.refresh_window_handle();
+    }
+
+    if window.is_null() {
+        return Err(WizWalkerError::WindowNotFound { pid: client.process_id() })
+    }
+
+    let window = window as HWND;
+    send_chars(window, username);
+    send_chars(window, "\t");
+    send_chars(window, password);
+    send_chars(window, "\r");
+
+    Ok(())
+}
+
+
+fn send_chars(window: HWND, chars: &str) {
+    for unit in chars.encode_utf16() {
+        unsafe { SendMessageW(window, WM_CHAR, unit as usize, 0) };
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launch_command_matches_the_launcher() {
+        let install_path = Path::new(r"C:\ProgramData\KingsIsle Entertainment\Wizard101");
+        let command = build_launch_command(install_path, LOGIN_SERVER, LOGIN_PORT);
+
+        assert_eq!(command.program, install_path.join("Bin").join("WizardGraphicalClient.exe"));
+        assert_eq!(command.working_directory, install_path.join("Bin"));
+        assert_eq!(command.args, vec!["-L", "login.us.wizard101.com", "12000"]);
+    }
+}
@@ -1,4 +1,9 @@
+pub mod error;
 pub mod memory;
 pub mod utils;
 pub mod client;
+pub mod client_handler;
+pub mod launcher;
+#[cfg(feature = "async")]
+pub mod async_client;
 pub mod file_readers;
\ No newline at end of file
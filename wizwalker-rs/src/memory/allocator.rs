@@ -0,0 +1,174 @@
+use winapi::um::winnt::{HANDLE, PAGE_EXECUTE_READWRITE};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::error::{Result, WizWalkerError};
+use super::assembler::is_rel32_reachable;
+use super::memory_reader::WizWalkerMemoryReader;
+
+
+/// Size of each region the pool reserves; one allocation granularity on Windows.
+pub const POOL_REGION_SIZE: usize = 0x10000;
+
+/// Alignment of every block handed out by the pool.
+pub const BLOCK_ALIGNMENT: usize = 0x10;
+
+
+/// Where hooks get their code and export memory from.
+///
+/// Kept object safe so hooks can share one allocator behind `Arc<Mutex<dyn HookAllocator>>`.
+pub trait HookAllocator: Send {
+    /// Allocates `size` bytes of executable memory, within `rel32` range of `near` when it is non-zero.
+    fn alloc_block(&mut self, near: usize, size: usize) -> Result<usize>;
+
+    /// Returns a block handed out by `alloc_block`.
+    fn free_block(&mut self, address: usize) -> Result<()>;
+}
+
+
+#[derive(Debug)]
+struct PoolRegion {
+    base: usize,
+    size: usize,
+    used: usize,
+    live_blocks: usize,
+}
+
+impl PoolRegion {
+    fn fits(&self, near: usize, size: usize) -> bool {
+        if self.used + size > self.size {
+            return false
+        }
+
+        near == 0 || (is_rel32_reachable(near, self.base) && is_rel32_reachable(self.base + self.size, near))
+    }
+}
+
+
+/// Sub-allocates small executable blocks from regions placed near their users.
+///
+/// Each region is bump allocated and released back to the process once every
+/// block in it was freed. Blocks are never freed automatically: dropping the
+/// pool while a hook still jumps into it would crash the client.
+pub struct ExecutablePool {
+    process: HANDLE,
+    symbol_table: HashMap<String, HashMap<String, u32>>,
+    regions: Vec<PoolRegion>,
+    blocks: HashMap<usize, usize>,
+}
+
+impl ExecutablePool {
+    pub fn new(process: HANDLE) -> Self {
+        Self {
+            process,
+            symbol_table: HashMap::new(),
+            regions: Vec::new(),
+            blocks: HashMap::new(),
+        }
+    }
+
+    /// Number of blocks currently handed out.
+    pub fn live_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Frees every region, including blocks still in use.
+    pub fn release_all(&mut self) -> Result<()> {
+        self.blocks.clear();
+
+        for region in std::mem::take(&mut self.regions) {
+            self.free(region.base)?;
+        }
+
+        Ok(())
+    }
+}
+
+// The process handle is only passed to thread safe Win32 calls.
+unsafe impl Send for ExecutablePool {}
+
+impl WizWalkerMemoryReader for ExecutablePool {
+    fn process(&self) -> HANDLE {
+        self.process
+    }
+
+    fn symbol_table(&mut self) -> &mut HashMap<String, HashMap<String, u32>> {
+        &mut self.symbol_table
+    }
+}
+
+impl HookAllocator for ExecutablePool {
+    fn alloc_block(&mut self, near: usize, size: usize) -> Result<usize> {
+        let size = size.max(1).next_multiple_of(BLOCK_ALIGNMENT);
+
+        let region_index = match self.regions.iter().position(|region| region.fits(near, size)) {
+            Some(index) => index,
+            None => {
+                let region_size = size.next_multiple_of(POOL_REGION_SIZE);
+                let base = match near {
+                    0 => self.allocate_with_protection(region_size, PAGE_EXECUTE_READWRITE)?,
+                    _ => self.allocate_near(near, region_size, PAGE_EXECUTE_READWRITE)?,
+                };
+
+                self.regions.push(PoolRegion {
+                    base,
+                    size: region_size,
+                    used: 0,
+                    live_blocks: 0,
+                });
+
+                self.regions.len() - 1
+            }
+        };
+
+        let region = &mut self.regions[region_index];
+        let address = region.base + region.used;
+        region.used += size;
+        region.live_blocks += 1;
+
+        self.blocks.insert(address, region.base);
+
+        Ok(address)
+    }
+
+    fn free_block(&mut self, address: usize) -> Result<()> {
+        let region_base = self.blocks.remove(&address).ok_or(WizWalkerError::FreeFailed { address })?;
+
+        let region_index = self.regions.iter()
+            .position(|region| region.base == region_base)
+            .ok_or(WizWalkerError::FreeFailed { address })?;
+
+        let region = &mut self.regions[region_index];
+        region.live_blocks -= 1;
+
+        if region.live_blocks == 0 {
+            self.regions.remove(region_index);
+            self.free(region_base)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// Pools shared by every hook on a process, keyed by process handle.
+///
+/// Held weakly, so a process's pool goes away with the last hook using it.
+static SHARED_POOLS: Mutex<Vec<(usize, Weak<Mutex<ExecutablePool>>)>> = Mutex::new(Vec::new());
+
+
+/// The executable pool shared by every hook on `process`, created on first use.
+pub fn shared_pool(process: HANDLE) -> Arc<Mutex<dyn HookAllocator>> {
+    let mut pools = SHARED_POOLS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    pools.retain(|(_, pool)| pool.strong_count() > 0);
+
+    if let Some(pool) = pools.iter().find(|(key, _)| *key == process as usize).and_then(|(_, pool)| pool.upgrade()) {
+        return pool
+    }
+
+    let pool = Arc::new(Mutex::new(ExecutablePool::new(process)));
+    pools.push((process as usize, Arc::downgrade(&pool)));
+
+    pool
+}
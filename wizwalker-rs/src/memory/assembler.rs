@@ -0,0 +1,397 @@
+use iced_x86::code_asm::CodeAssembler;
+use iced_x86::{BlockEncoder, BlockEncoderOptions, Decoder, DecoderOptions, FlowControl, Instruction, InstructionBlock};
+
+use crate::error::{Result, WizWalkerError};
+
+
+/// `jmp rel32`
+pub const RELATIVE_JUMP_SIZE: usize = 5;
+
+/// `jmp qword ptr [rip]` followed by the 8 byte target.
+pub const ABSOLUTE_JUMP_SIZE: usize = 14;
+
+/// Longest possible x86-64 instruction.
+pub const MAX_INSTRUCTION_SIZE: usize = 15;
+
+/// Bytes after a hook site checked for branches back into the patched instructions.
+pub const BRANCH_SCAN_WINDOW: usize = 0x200;
+
+/// Room reserved past the hook body for the relocated stolen instructions and the return jump.
+pub const HOOK_SIZE_SLACK: usize = 64;
+
+
+/// Displacement of a `rel32` operand ending at `from_end` and pointing at `to`, if it fits.
+pub fn rel32_displacement(from_end: usize, to: usize) -> Option<i32> {
+    i32::try_from(to as i64 - from_end as i64).ok()
+}
+
+
+/// Whether a 5 byte `jmp rel32` placed at `from` can reach `to`.
+pub fn is_rel32_reachable(from: usize, to: usize) -> bool {
+    rel32_displacement(from + RELATIVE_JUMP_SIZE, to).is_some()
+}
+
+
+pub fn relative_jump(from: usize, to: usize) -> Result<Vec<u8>> {
+    let displacement = rel32_displacement(from + RELATIVE_JUMP_SIZE, to)
+        .ok_or(WizWalkerError::JumpOutOfRange { from, to })?;
+
+    let mut bytecode = vec![0xE9];
+    bytecode.extend_from_slice(&displacement.to_le_bytes());
+
+    Ok(bytecode)
+}
+
+
+/// A jump that reaches anywhere without clobbering a register.
+pub fn absolute_jump(to: usize) -> Vec<u8> {
+    let mut bytecode = vec![0xFF, 0x25, 0x00, 0x00, 0x00, 0x00];
+    bytecode.extend_from_slice(&(to as u64).to_le_bytes());
+
+    bytecode
+}
+
+
+/// The shortest jump from `from` to `to`: `rel32` when in range, absolute otherwise.
+pub fn jump(from: usize, to: usize) -> Vec<u8> {
+    relative_jump(from, to).unwrap_or_else(|_| absolute_jump(to))
+}
+
+
+/// Decodes `bytes` as if they were located at `ip`.
+///
+/// Fails on invalid or truncated instructions, so it doubles as a check that
+/// `bytes` ends on an instruction boundary.
+pub fn decode_instructions(bytes: &[u8], ip: usize) -> Result<Vec<Instruction>> {
+    let mut decoder = Decoder::with_ip(64, bytes, ip as u64, DecoderOptions::NONE);
+    let mut instructions = Vec::new();
+
+    while decoder.can_decode() {
+        let instruction = decoder.decode();
+
+        if instruction.is_invalid() {
+            return Err(WizWalkerError::InvalidInstruction { address: instruction.ip() as usize })
+        }
+
+        instructions.push(instruction);
+    }
+
+    Ok(instructions)
+}
+
+
+/// Re-encodes instructions to run from `new_ip`.
+///
+/// RIP-relative operands and branches are rewritten to keep pointing at
+/// their original targets; short branches are widened when needed.
+pub fn encode_instructions(instructions: &[Instruction], new_ip: usize) -> Result<Vec<u8>> {
+    if instructions.is_empty() {
+        return Ok(Vec::new())
+    }
+
+    let block = InstructionBlock::new(instructions, new_ip as u64);
+    let encoded = BlockEncoder::encode(64, block, BlockEncoderOptions::NONE)?;
+
+    Ok(encoded.code_buffer)
+}
+
+
+/// Moves the machine code in `bytes` from `old_ip` to `new_ip`.
+pub fn relocate_instructions(bytes: &[u8], old_ip: usize, new_ip: usize) -> Result<Vec<u8>> {
+    let instructions = decode_instructions(bytes, old_ip)?;
+
+    encode_instructions(&instructions, new_ip)
+}
+
+
+/// Whole instructions taken from a hook site to make room for the jump.
+#[derive(Debug, Clone)]
+pub struct StolenInstructions {
+    pub address: usize,
+    pub bytecode: Vec<u8>,
+    pub instructions: Vec<Instruction>,
+}
+
+impl StolenInstructions {
+    pub fn len(&self) -> usize {
+        self.bytecode.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytecode.is_empty()
+    }
+
+    fn contains_inner(&self, target: u64) -> bool {
+        let start = self.address as u64;
+        target > start && target < start + self.bytecode.len() as u64
+    }
+}
+
+
+/// Picks the whole instructions at `address` that a jump of `jump_size` bytes overwrites.
+///
+/// `code` is the memory starting at `address`; anything past the stolen
+/// instructions (up to `BRANCH_SCAN_WINDOW`) is scanned for branches. The site
+/// is refused when the jump would reach past a `ret` or unconditional jump
+/// (the bytes after it may belong to another function) or when any decoded
+/// branch lands inside the stolen instructions, since that code would then
+/// execute half of our jump.
+pub fn steal_instructions(code: &[u8], address: usize, jump_size: usize) -> Result<StolenInstructions> {
+    let mut decoder = Decoder::with_ip(64, code, address as u64, DecoderOptions::NONE);
+    let mut instructions: Vec<Instruction> = Vec::new();
+    let mut stolen_size = 0;
+
+    while stolen_size < jump_size {
+        if !decoder.can_decode() {
+            return Err(WizWalkerError::InvalidInstruction { address: address + stolen_size })
+        }
+
+        let instruction = decoder.decode();
+        if instruction.is_invalid() {
+            return Err(WizWalkerError::InvalidInstruction { address: instruction.ip() as usize })
+        }
+
+        let ends_flow = matches!(
+            instruction.flow_control(),
+            FlowControl::Return | FlowControl::UnconditionalBranch | FlowControl::IndirectBranch | FlowControl::Interrupt
+        );
+
+        stolen_size += instruction.len();
+        instructions.push(instruction);
+
+        if ends_flow && stolen_size < jump_size {
+            return Err(WizWalkerError::UnsafeHookSite {
+                address,
+                reason: format!("the jump would overwrite past the end of the code at \"{:#x}\"", instruction.ip()),
+            })
+        }
+    }
+
+    let stolen = StolenInstructions {
+        address,
+        bytecode: code[..stolen_size].to_vec(),
+        instructions,
+    };
+
+    let scanned = stolen.instructions.iter()
+        .copied()
+        .chain(decoder.iter().take_while(|instruction| {
+            !instruction.is_invalid() && instruction.ip() < (address + stolen_size + BRANCH_SCAN_WINDOW) as u64
+        }));
+
+    for instruction in scanned {
+        let target = instruction.near_branch_target();
+        if target != 0 && stolen.contains_inner(target) {
+            return Err(WizWalkerError::UnsafeHookSite {
+                address,
+                reason: format!("the instruction at \"{:#x}\" branches into the patched bytes at \"{:#x}\"", instruction.ip(), target),
+            })
+        }
+    }
+
+    Ok(stolen)
+}
+
+
+/// Pads `bytecode` with `nop`s up to `size` bytes so no partial instruction is left behind.
+pub fn pad_with_nops(mut bytecode: Vec<u8>, size: usize) -> Vec<u8> {
+    if bytecode.len() < size {
+        bytecode.resize(size, 0x90);
+    }
+
+    bytecode
+}
+
+
+/// Lays out a hook trampoline at `hook_address`.
+///
+/// The generated code is the hook body, then the instructions stolen from the
+/// jump site relocated to follow it, then a jump back to the instruction after
+/// the stolen ones. To skip to the original code from the body, jump to a
+/// label placed on a trailing `nop`.
+pub struct HookCodeBuilder {
+    hook_address: usize,
+    body: CodeAssembler,
+    stolen_bytecode: Vec<u8>,
+    stolen_address: usize,
+    return_address: Option<usize>,
+}
+
+impl HookCodeBuilder {
+    pub fn new(hook_address: usize) -> Result<Self> {
+        Ok(Self {
+            hook_address,
+            body: CodeAssembler::new(64)?,
+            stolen_bytecode: Vec::new(),
+            stolen_address: 0,
+            return_address: None,
+        })
+    }
+
+    pub fn body(&mut self) -> &mut CodeAssembler {
+        &mut self.body
+    }
+
+    /// Sets the original instructions displaced by the jump and returns after them.
+    pub fn stolen(mut self, bytecode: &[u8], address: usize) -> Self {
+        self.stolen_bytecode = bytecode.to_vec();
+        self.stolen_address = address;
+        self.return_address = Some(address + bytecode.len());
+        self
+    }
+
+    /// Overrides where the trampoline jumps once done.
+    pub fn return_to(mut self, address: usize) -> Self {
+        self.return_address = Some(address);
+        self
+    }
+
+    pub fn build(mut self) -> Result<Vec<u8>> {
+        let mut bytecode = match self.body.instructions().is_empty() {
+            true => Vec::new(),
+            false => self.body.assemble(self.hook_address as u64)?,
+        };
+
+        let stolen_ip = self.hook_address + bytecode.len();
+        bytecode.extend(relocate_instructions(&self.stolen_bytecode, self.stolen_address, stolen_ip)?);
+
+        if let Some(return_address) = self.return_address {
+            let return_jump = jump(self.hook_address + bytecode.len(), return_address);
+            bytecode.extend(return_jump);
+        }
+
+        Ok(bytecode)
+    }
+}
+
+
+/// Size of `body` once assembled near `ip`; used to size hook allocations before the final address is known.
+pub fn assembled_size(body: &mut CodeAssembler, ip: usize) -> Result<usize> {
+    if body.instructions().is_empty() {
+        return Ok(0)
+    }
+
+    Ok(body.assemble(ip as u64)?.len())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced_x86::{Code, Register};
+
+    const SITE: usize = 0x1_4000_1000;
+
+    #[test]
+    fn relative_jumps_reject_targets_out_of_rel32_range() {
+        assert_eq!(relative_jump(SITE, SITE + 0x105).unwrap(), [0xE9, 0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(relative_jump(SITE, SITE).unwrap(), [0xE9, 0xFB, 0xFF, 0xFF, 0xFF]);
+
+        let far = SITE + 0x1_0000_0000;
+        assert!(!is_rel32_reachable(SITE, far));
+        assert!(matches!(relative_jump(SITE, far), Err(WizWalkerError::JumpOutOfRange { .. })));
+
+        let fallback = jump(SITE, far);
+        assert_eq!(fallback.len(), ABSOLUTE_JUMP_SIZE);
+        assert_eq!(fallback[6..], (far as u64).to_le_bytes());
+    }
+
+    #[test]
+    fn relocation_keeps_rip_relative_operands_on_their_targets() {
+        // mov rax, [rip+0x100]; lea rcx, [rip-0x20]
+        let code = [0x48, 0x8B, 0x05, 0x00, 0x01, 0x00, 0x00, 0x48, 0x8D, 0x0D, 0xE0, 0xFF, 0xFF, 0xFF];
+        let original = decode_instructions(&code, SITE).unwrap();
+
+        let new_ip = SITE - 0x1000_0000;
+        let relocated = relocate_instructions(&code, SITE, new_ip).unwrap();
+        let moved = decode_instructions(&relocated, new_ip).unwrap();
+
+        assert_eq!(moved.len(), 2);
+        for (before, after) in original.iter().zip(&moved) {
+            assert_eq!(after.code(), before.code());
+            assert_eq!(after.memory_base(), Register::RIP);
+            assert_eq!(after.memory_displacement64(), before.memory_displacement64());
+        }
+
+        assert_eq!(moved[0].memory_displacement64() as usize, SITE + 7 + 0x100);
+    }
+
+    #[test]
+    fn relocation_widens_short_branches_that_no_longer_reach() {
+        // jz +0x10
+        let code = [0x74, 0x10];
+        let target = SITE + 2 + 0x10;
+
+        let new_ip = SITE + 0x1000_0000;
+        let relocated = relocate_instructions(&code, SITE, new_ip).unwrap();
+        let moved = decode_instructions(&relocated, new_ip).unwrap();
+
+        assert_eq!(moved[0].code(), Code::Je_rel32_64);
+        assert_eq!(moved[0].near_branch_target() as usize, target);
+    }
+
+    #[test]
+    fn trampolines_run_the_body_then_the_stolen_code_then_return() {
+        // mov eax, 1; push rbx
+        let stolen = [0xB8, 0x01, 0x00, 0x00, 0x00, 0x53];
+        let hook_address = SITE + 0x10_0000;
+
+        let mut builder = HookCodeBuilder::new(hook_address).unwrap().stolen(&stolen, SITE);
+        builder.body().nop().unwrap();
+        let bytecode = builder.build().unwrap();
+
+        let instructions = decode_instructions(&bytecode, hook_address).unwrap();
+        let codes: Vec<Code> = instructions.iter().map(|instruction| instruction.code()).collect();
+
+        assert_eq!(codes, [Code::Nopd, Code::Mov_r32_imm32, Code::Push_r64, Code::Jmp_rel32_64]);
+        assert_eq!(instructions[3].near_branch_target() as usize, SITE + stolen.len());
+    }
+
+    #[test]
+    fn stealing_takes_whole_instructions() {
+        // push rbx; sub rsp, 0x20; mov rbx, rcx; ret
+        let code = [0x53, 0x48, 0x83, 0xEC, 0x20, 0x48, 0x8B, 0xD9, 0xC3];
+
+        let stolen = steal_instructions(&code, SITE, RELATIVE_JUMP_SIZE).unwrap();
+        assert_eq!(stolen.len(), 5);
+        assert_eq!(stolen.instructions.len(), 2);
+
+        let stolen = steal_instructions(&code, SITE, 6).unwrap();
+        assert_eq!(stolen.bytecode, code[..8]);
+    }
+
+    #[test]
+    fn stealing_refuses_to_run_past_a_ret_or_jmp() {
+        // xor eax, eax; ret; int3 ...
+        let ret = [0x31, 0xC0, 0xC3, 0xCC, 0xCC, 0xCC, 0xCC];
+        assert!(matches!(steal_instructions(&ret, SITE, RELATIVE_JUMP_SIZE), Err(WizWalkerError::UnsafeHookSite { .. })));
+
+        // jmp +0x10; int3 ...
+        let jmp = [0xEB, 0x10, 0xCC, 0xCC, 0xCC, 0xCC, 0xCC];
+        assert!(matches!(steal_instructions(&jmp, SITE, RELATIVE_JUMP_SIZE), Err(WizWalkerError::UnsafeHookSite { .. })));
+
+        // A ret that is itself the last stolen byte is fine: mov eax, 1; ret
+        let last = [0xB8, 0x01, 0x00, 0x00, 0x00, 0xC3];
+        assert!(steal_instructions(&last, SITE, 6).is_ok());
+    }
+
+    #[test]
+    fn stealing_refuses_sites_that_are_branched_into() {
+        // push rbx; push rsi; push rdi; sub rsp, 0x20; jnz back to push rsi
+        let code = [0x53, 0x56, 0x57, 0x48, 0x83, 0xEC, 0x20, 0x75, 0xF8];
+        assert!(matches!(steal_instructions(&code, SITE, RELATIVE_JUMP_SIZE), Err(WizWalkerError::UnsafeHookSite { .. })));
+
+        // Branching to the first stolen instruction lands on the jump itself, which is fine.
+        let code = [0x53, 0x56, 0x57, 0x48, 0x83, 0xEC, 0x20, 0x75, 0xF7];
+        assert!(steal_instructions(&code, SITE, RELATIVE_JUMP_SIZE).is_ok());
+    }
+
+    #[test]
+    fn padding_fills_the_stolen_bytes_with_nops() {
+        let padded = pad_with_nops(vec![0xE9, 0, 0, 0, 0], 8);
+        assert_eq!(padded, [0xE9, 0, 0, 0, 0, 0x90, 0x90, 0x90]);
+
+        assert_eq!(pad_with_nops(vec![0xE9, 0, 0, 0, 0], 5).len(), 5);
+        assert_eq!(pad_with_nops(vec![0xE9, 0, 0, 0, 0], 3).len(), 5);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::remote_struct;
+use super::pointers::RemotePtr;
+
+
+/// Upper bound on elements read from any container.
+///
+/// Protects against walking garbage when a container is read mid-update or from a bad address.
+pub const MAX_CONTAINER_SIZE: usize = 0x10_0000;
+
+/// Size of the inline buffer of an MSVC `std::basic_string`, in bytes.
+pub const STRING_SSO_BUFFER_SIZE: usize = 16;
+
+
+remote_struct! {
+    /// MSVC x64 `std::basic_string` (`std::string` and `std::wstring`).
+    ///
+    /// Strings with a capacity smaller than the inline buffer are stored in place
+    /// (small string optimization); otherwise `buffer` holds a heap pointer.
+    #[derive(Debug, Clone, Copy)]
+    pub struct StdStringLayout {
+        0x00 => pub buffer: [u8; STRING_SSO_BUFFER_SIZE],
+        0x10 => pub size: u64,
+        0x18 => pub capacity: u64,
+    }
+}
+
+impl StdStringLayout {
+    /// Whether the characters live inside `buffer` for characters of `char_size` bytes.
+    pub fn is_inline(&self, char_size: usize) -> bool {
+        (self.capacity as usize) < STRING_SSO_BUFFER_SIZE / char_size
+    }
+
+    pub fn heap_pointer(&self) -> usize {
+        u64::from_le_bytes(self.buffer[..8].try_into().unwrap_or_default()) as usize
+    }
+}
+
+
+remote_struct! {
+    /// MSVC x64 `std::vector`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct StdVectorLayout {
+        0x00 => pub first: u64,
+        0x08 => pub last: u64,
+        0x10 => pub end: u64,
+    }
+}
+
+impl StdVectorLayout {
+    pub fn byte_len(&self) -> usize {
+        self.last.saturating_sub(self.first) as usize
+    }
+}
+
+
+remote_struct! {
+    /// MSVC x64 `std::list` and `std::map` header: sentinel node and element count.
+    #[derive(Debug, Clone, Copy)]
+    pub struct StdNodeContainerLayout {
+        0x00 => pub head: u64,
+        0x08 => pub size: u64,
+    }
+}
+
+
+remote_struct! {
+    /// MSVC x64 `std::list` node. The value starts at `STD_LIST_NODE_VALUE_OFFSET`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct StdListNodeLayout {
+        0x00 => pub next: u64,
+        0x08 => pub prev: u64,
+    }
+}
+
+pub const STD_LIST_NODE_VALUE_OFFSET: usize = 0x10;
+
+
+remote_struct! {
+    /// MSVC x64 `std::map` red-black tree node. The key/value pair follows `is_nil`; see `std_map_node_pair_offset`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct StdMapNodeLayout {
+        0x00 => pub left: u64,
+        0x08 => pub parent: u64,
+        0x10 => pub right: u64,
+        0x18 => pub color: u8,
+        0x19 => pub is_nil: u8,
+    }
+}
+
+/// First byte after `StdMapNodeLayout::is_nil`.
+const STD_MAP_NODE_HEADER_SIZE: usize = 0x1A;
+
+
+/// Offset of the key/value pair in a `std::map` node, for a pair aligned to `pair_align` bytes.
+///
+/// 0x1C for `std::map<u32, u32>`, 0x20 once either side is 8 byte aligned.
+pub const fn std_map_node_pair_offset(pair_align: usize) -> usize {
+    STD_MAP_NODE_HEADER_SIZE.next_multiple_of(pair_align)
+}
+
+
+remote_struct! {
+    /// `std::shared_ptr` (KingsIsle `SharedPointer`): object pointer and control block.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct SharedPointer {
+        0x00 => pub pointer: u64,
+        0x08 => pub control_block: u64,
+    }
+}
+
+impl SharedPointer {
+    pub fn is_null(&self) -> bool {
+        self.pointer == 0
+    }
+
+    pub fn as_ptr<T>(&self) -> RemotePtr<T> {
+        RemotePtr::new(self.pointer as usize)
+    }
+}
+
+
+/// Alignment of a `std::pair<K, V>`, the stricter of its two sides.
+pub const fn std_pair_align<K, V>() -> usize {
+    let key_align = std::mem::align_of::<K>();
+    let value_align = std::mem::align_of::<V>();
+
+    if key_align > value_align { key_align } else { value_align }
+}
+
+
+/// Offset of the value in a `std::map<K, V>` pair, following C++ alignment rules.
+pub const fn std_pair_value_offset<K, V>() -> usize {
+    let key_size = std::mem::size_of::<K>();
+    let align = std::mem::align_of::<V>();
+
+    key_size.div_ceil(align) * align
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Pod;
+    use crate::error::WizWalkerError;
+    use crate::memory::fake_memory::{FakeMemory, FAKE_MODULE_BASE};
+    use crate::memory::memory_reader::WizWalkerMemoryReader;
+
+    const CONTAINER: usize = FAKE_MODULE_BASE;
+    const NODES: usize = FAKE_MODULE_BASE + 0x1000;
+    const NODE_SIZE: usize = 0x40;
+
+    fn node(index: usize) -> usize {
+        NODES + index * NODE_SIZE
+    }
+
+    fn container_memory() -> FakeMemory {
+        FakeMemory::with_zeroed_regions(&[(CONTAINER, 0x100), (NODES, 8 * NODE_SIZE)])
+    }
+
+    fn write_map_node(memory: &mut FakeMemory, address: usize, links: [usize; 3], is_nil: bool) {
+        memory.write_typed(address, links.map(|link| link as u64)).unwrap();
+        memory.write_typed(address + 0x19, is_nil as u8).unwrap();
+    }
+
+    /// A map of three entries, with pairs `pair_offset` into each node: 2 at the root, 1 and 3 below it.
+    fn write_map<K: Pod + Send, V: Pod + Send>(memory: &mut FakeMemory, pair_offset: usize, entries: [(K, V); 3]) {
+        let (head, one, two, three) = (node(0), node(1), node(2), node(3));
+
+        memory.write_typed(CONTAINER, [head as u64, 3]).unwrap();
+        write_map_node(memory, head, [one, two, three], true);
+        write_map_node(memory, one, [head, two, head], false);
+        write_map_node(memory, two, [one, head, three], false);
+        write_map_node(memory, three, [head, two, head], false);
+
+        for (node, (key, value)) in [one, two, three].into_iter().zip(entries) {
+            memory.write_typed(node + pair_offset, key).unwrap();
+            memory.write_typed(node + pair_offset + std_pair_value_offset::<K, V>(), value).unwrap();
+        }
+    }
+
+    #[test]
+    fn vectors_read_every_element() {
+        let mut memory = container_memory();
+
+        memory.write_typed(NODES, [1u32, 2, 3]).unwrap();
+        memory.write_typed(CONTAINER, [NODES as u64, NODES as u64 + 12, NODES as u64 + 16]).unwrap();
+        assert_eq!(memory.read_std_vector::<u32>(CONTAINER).unwrap(), [1, 2, 3]);
+
+        memory.write_typed(CONTAINER + 8, NODES as u64 + 10).unwrap();
+        assert!(matches!(memory.read_std_vector::<u32>(CONTAINER), Err(WizWalkerError::InvalidData { .. })));
+    }
+
+    #[test]
+    fn lists_follow_next_until_the_sentinel() {
+        let mut memory = container_memory();
+        let (head, first, second) = (node(0), node(1), node(2));
+
+        memory.write_typed(CONTAINER, [head as u64, 2]).unwrap();
+        memory.write_typed(head, [first as u64, second as u64]).unwrap();
+        memory.write_typed(first, [second as u64, head as u64]).unwrap();
+        memory.write_typed(second, [head as u64, first as u64]).unwrap();
+        memory.write_typed(first + STD_LIST_NODE_VALUE_OFFSET, 10u64).unwrap();
+        memory.write_typed(second + STD_LIST_NODE_VALUE_OFFSET, 20u64).unwrap();
+
+        assert_eq!(memory.read_std_list::<u64>(CONTAINER).unwrap(), [10, 20]);
+    }
+
+    #[test]
+    fn maps_place_pairs_by_their_alignment() {
+        assert_eq!(std_map_node_pair_offset(std_pair_align::<u32, u32>()), 0x1C);
+        assert_eq!(std_map_node_pair_offset(std_pair_align::<u32, u64>()), 0x20);
+        assert_eq!(std_map_node_pair_offset(std_pair_align::<u8, u8>()), 0x1A);
+
+        let mut memory = container_memory();
+        write_map(&mut memory, 0x1C, [(1u32, 10u32), (2, 20), (3, 30)]);
+        assert_eq!(memory.read_std_map::<u32, u32>(CONTAINER).unwrap(), [(1, 10), (2, 20), (3, 30)]);
+
+        let mut memory = container_memory();
+        write_map(&mut memory, 0x20, [(1u32, 10u64), (2, 20), (3, 30)]);
+        assert_eq!(memory.read_std_map::<u32, u64>(CONTAINER).unwrap(), [(1, 10), (2, 20), (3, 30)]);
+    }
+}
@@ -0,0 +1,225 @@
+//! An in-process stand-in for a client's memory, so code built on
+//! `WizWalkerMemoryReader` can be tested without a running game.
+
+use regex::bytes::Regex;
+use winapi::um::winnt::{HANDLE, MEMORY_BASIC_INFORMATION, PAGE_EXECUTE_READWRITE};
+
+use std::collections::{BTreeMap, HashMap};
+use std::ptr::null_mut;
+use std::sync::{Arc, Mutex};
+
+use crate::error::{Result, WizWalkerError};
+use super::allocator::HookAllocator;
+use super::memory_reader::WizWalkerMemoryReader;
+use super::read_cache::{page_of, PAGE_SIZE};
+
+
+/// Where fake allocations start; within `rel32` range of `FAKE_MODULE_BASE`.
+pub const FAKE_ALLOCATION_BASE: usize = 0x1_0000_0000;
+
+/// Where tests usually map their fake client module.
+pub const FAKE_MODULE_BASE: usize = 0x1_4000_0000;
+
+
+#[derive(Default)]
+struct FakeState {
+    regions: BTreeMap<usize, Vec<u8>>,
+    modules: HashMap<String, (usize, usize)>,
+    protections: BTreeMap<usize, u32>,
+    writes: Vec<(usize, usize)>,
+    next_allocation: usize,
+}
+
+impl FakeState {
+    fn region_containing(&mut self, address: usize, size: usize) -> Option<(usize, &mut Vec<u8>)> {
+        let (base, data) = self.regions.range_mut(..=address).next_back()?;
+
+        match address + size <= base + data.len() {
+            true => Some((address - base, data)),
+            false => None
+        }
+    }
+}
+
+
+/// Sparse memory made of mapped regions.
+///
+/// Clones share the same memory, so a test can keep one to inspect what a
+/// hook wrote through another. Protection changes always succeed and are
+/// recorded per page, with untouched pages read-write-execute. Instruction
+/// cache flushes always succeed, and it doubles as a `HookAllocator`.
+#[derive(Clone)]
+pub struct FakeMemory {
+    state: Arc<Mutex<FakeState>>,
+    symbol_table: HashMap<String, HashMap<String, u32>>,
+}
+
+impl FakeMemory {
+    pub fn new() -> Self {
+        let state = FakeState {
+            next_allocation: FAKE_ALLOCATION_BASE,
+            ..FakeState::default()
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            symbol_table: HashMap::new(),
+        }
+    }
+
+    pub fn map(&self, address: usize, bytes: Vec<u8>) {
+        self.state.lock().unwrap().regions.insert(address, bytes);
+    }
+
+    /// Memory with a zeroed region of `size` bytes at each `(address, size)`.
+    pub fn with_zeroed_regions(regions: &[(usize, usize)]) -> Self {
+        let memory = Self::new();
+        for (address, size) in regions {
+            memory.map(*address, vec![0; *size]);
+        }
+
+        memory
+    }
+
+    /// Maps `bytes` as a module, making it visible to `pattern_scan` by name.
+    pub fn map_module(&self, name: &str, address: usize, bytes: Vec<u8>) {
+        let mut state = self.state.lock().unwrap();
+        state.modules.insert(name.to_string(), (address, bytes.len()));
+        state.regions.insert(address, bytes);
+    }
+
+    pub fn is_mapped(&self, address: usize) -> bool {
+        self.state.lock().unwrap().region_containing(address, 1).is_some()
+    }
+
+    /// The `(address, size)` of every write so far, oldest first.
+    pub fn writes(&self) -> Vec<(usize, usize)> {
+        self.state.lock().unwrap().writes.clone()
+    }
+
+    /// The protection of the page holding `address`.
+    pub fn protection(&self, address: usize) -> u32 {
+        let state = self.state.lock().unwrap();
+        state.protections.get(&page_of(address)).copied().unwrap_or(PAGE_EXECUTE_READWRITE)
+    }
+}
+
+impl WizWalkerMemoryReader for FakeMemory {
+    fn process(&self) -> HANDLE {
+        null_mut()
+    }
+
+    fn symbol_table(&mut self) -> &mut HashMap<String, HashMap<String, u32>> {
+        &mut self.symbol_table
+    }
+
+    fn is_running(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn pattern_scan(&mut self, pattern: &str, module_name_opt: Option<&str>, return_multiple: bool) -> Result<Vec<usize>> {
+        let regex = Regex::new(pattern)?;
+        let state = self.state.lock()?;
+
+        let bounds = match module_name_opt {
+            Some(module_name) => Some(*state.modules.get(module_name)
+                .ok_or(WizWalkerError::ModuleNotFound { module: module_name.to_string() })?),
+            None => None
+        };
+
+        let mut found: Vec<usize> = Vec::new();
+        for (base, data) in state.regions.iter() {
+            if let Some((module_base, module_size)) = bounds {
+                if *base < module_base || *base >= module_base + module_size {
+                    continue
+                }
+            }
+
+            found.extend(regex.find_iter(data).map(|mat| base + mat.start()));
+        }
+
+        if found.is_empty() {
+            return Err(WizWalkerError::PatternNotFound { pattern: pattern.to_string() })
+        }
+
+        if found.len() > 1 && !return_multiple {
+            return Err(WizWalkerError::PatternNotUnique { pattern: pattern.to_string(), count: found.len() })
+        }
+
+        Ok(found)
+    }
+
+    fn allocate_with_protection(&mut self, size: usize, _protection: u32) -> Result<usize> {
+        let mut state = self.state.lock()?;
+
+        let address = state.next_allocation;
+        state.next_allocation += size.next_multiple_of(0x1000);
+        state.regions.insert(address, vec![0; size]);
+
+        Ok(address)
+    }
+
+    fn query_region(&mut self, address: usize) -> Result<MEMORY_BASIC_INFORMATION> {
+        Err(WizWalkerError::QueryFailed { address })
+    }
+
+    fn allocate_near(&mut self, _address: usize, size: usize, protection: u32) -> Result<usize> {
+        self.allocate_with_protection(size, protection)
+    }
+
+    fn free(&mut self, address: usize) -> Result<()> {
+        match self.state.lock()?.regions.remove(&address) {
+            Some(_) => Ok(()),
+            None => Err(WizWalkerError::FreeFailed { address })
+        }
+    }
+
+    fn start_thread(&mut self, address: usize) -> Result<()> {
+        Err(WizWalkerError::ThreadCreationFailed { address })
+    }
+
+    fn read_bytes(&mut self, address: usize, size: usize) -> Result<Vec<u8>> {
+        let mut state = self.state.lock()?;
+        let (offset, data) = state.region_containing(address, size)
+            .ok_or(WizWalkerError::ReadFailed { address, size })?;
+
+        Ok(data[offset..offset + size].to_vec())
+    }
+
+    fn write_bytes(&mut self, address: usize, value: Vec<u8>) -> Result<()> {
+        let size = value.len();
+        let mut state = self.state.lock()?;
+        let (offset, data) = state.region_containing(address, size)
+            .ok_or(WizWalkerError::WriteFailed { address, size })?;
+
+        data[offset..offset + size].copy_from_slice(&value);
+        state.writes.push((address, size));
+
+        Ok(())
+    }
+
+    fn protect(&mut self, address: usize, size: usize, protection: u32) -> Result<u32> {
+        let old_protection = self.protection(address);
+
+        let mut state = self.state.lock()?;
+        for page in (page_of(address)..address + size.max(1)).step_by(PAGE_SIZE) {
+            state.protections.insert(page, protection);
+        }
+
+        Ok(old_protection)
+    }
+
+    fn flush_instruction_cache(&mut self, _address: usize, _size: usize) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl HookAllocator for FakeMemory {
+    fn alloc_block(&mut self, near: usize, size: usize) -> Result<usize> {
+        self.allocate_near(near, size, PAGE_EXECUTE_READWRITE)
+    }
+
+    fn free_block(&mut self, address: usize) -> Result<()> {
+        self.free(address)
+    }
+}
@@ -1,24 +1,348 @@
-use crate::memory::memory_reader::WizWalkerMemoryReader;
-use std::sync::{Arc, Mutex};
-
-const AUTOBOT_PATTERN: &[u8] = &[
-    0x48, 0x8B, 0xC4, 0x55, 0x41, 0x54, 0x41, 0x55, 0x41, 0x56, 0x41, 0x57,
-    0x48, // ...
-    0x48, // ...
-    0x48, 0x89, 0x58, 0x10, 0x48, 0x89, 0x70, 0x18, 0x48, 0x89, 0x78, 0x20,
-    0x48, 0x33, 0xC4, // ...
-    0x4C, 0x8B, 0xE9, // .......
-    0x80, // ...
-    0x0F,
-];
-
-const AUTOBOT_SIZE: usize = 3900;
+use crate::error::{Result, WizWalkerError};
+use crate::memory::memory_reader::{restore_protections, WizWalkerMemoryReader};
+use winapi::um::winnt::{HANDLE, PAGE_EXECUTE_READWRITE};
 
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::allocator::{HookAllocator, BLOCK_ALIGNMENT};
+use super::hooks::wait_for_export;
+use super::read_cache::{page_of, PAGE_SIZE};
+use super::registry::{register_emergency_restore, unregister_emergency_restore};
+use super::signatures::AUTOBOT;
+
+
+/// Bytes of the autobot function available to hooks.
+pub const AUTOBOT_SIZE: usize = 3900;
+
+/// How often `wait_for_hook_value` callers usually poll.
+pub const DEFAULT_HOOK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long callers usually wait for a hook export before giving up.
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Bytes kept at the start of the autobot function for the `ret` that disables it.
+const AUTOBOT_RESERVED: usize = BLOCK_ALIGNMENT;
+
+
+/// Shares the client's unused autobot function as a code cave for every hook.
+///
+/// The first allocation finds the function, backs up its `AUTOBOT_SIZE`
+/// bytes and turns it into a bare `ret` so nothing runs the leftovers. The
+/// cave stays read-write-execute while it is taken over, since hooks store
+/// their exports next to their code. Blocks are handed out by bumping
+/// `autobot_pos`; once the last one is freed the original bytes and
+/// protection are put back and the cave can be set up again.
 pub trait WizWalkerHookHandler: WizWalkerMemoryReader {
-    fn autobot_address(&mut self) -> Arc<Mutex<&mut usize>>;
-    fn original_autobot_bytes(&mut self) -> Arc<Mutex<&mut Vec<u8>>>;
+    fn autobot_address(&mut self) -> &mut Option<usize>;
+    fn original_autobot_bytes(&mut self) -> &mut Vec<u8>;
+    fn original_autobot_protections(&mut self) -> &mut Vec<(usize, u32)>;
     fn autobot_pos(&mut self) -> &mut usize;
-    // fn active_hooks
+    fn autobot_blocks(&mut self) -> &mut Vec<usize>;
+
+    /// The autobot function, taking it over on first use.
+    fn get_autobot_address(&mut self) -> Result<usize> {
+        if let Some(address) = *self.autobot_address() {
+            return Ok(address)
+        }
+
+        let address = *self.pattern_scan(AUTOBOT.pattern, Some(AUTOBOT.module), false)?
+            .first()
+            .ok_or(WizWalkerError::PatternNotFound { pattern: AUTOBOT.pattern.to_string() })?;
+
+        let original_bytes = self.read_bytes(address, AUTOBOT_SIZE)?;
+
+        let mut old_protections: Vec<(usize, u32)> = Vec::new();
+        for page in (page_of(address)..address + AUTOBOT_SIZE).step_by(PAGE_SIZE) {
+            match self.protect(page, PAGE_SIZE, PAGE_EXECUTE_READWRITE) {
+                Ok(old_protection) => old_protections.push((page, old_protection)),
+                Err(error) => {
+                    restore_protections(self, &old_protections)?;
+                    return Err(error)
+                }
+            }
+        }
+
+        if let Err(error) = self.write_bytes_guarded(address, vec![0xC3]) {
+            restore_protections(self, &old_protections)?;
+            return Err(error)
+        }
+
+        register_emergency_restore(self.process(), address, original_bytes.clone());
+
+        *self.original_autobot_bytes() = original_bytes;
+        *self.original_autobot_protections() = old_protections;
+        *self.autobot_address() = Some(address);
+        *self.autobot_pos() = AUTOBOT_RESERVED;
+
+        Ok(address)
+    }
+
+    /// Hands out `size` zeroed bytes of the code cave.
+    fn allocate_autobot_bytes(&mut self, size: usize) -> Result<usize> {
+        let size = size.max(1).next_multiple_of(BLOCK_ALIGNMENT);
+        let autobot_address = self.get_autobot_address()?;
+        let position = *self.autobot_pos();
+
+        if position + size > AUTOBOT_SIZE {
+            return Err(WizWalkerError::HookTooLarge { size, allocated: AUTOBOT_SIZE - position })
+        }
+
+        let address = autobot_address + position;
+        self.write_bytes_guarded(address, vec![0; size])?;
+
+        *self.autobot_pos() += size;
+        self.autobot_blocks().push(address);
+
+        Ok(address)
+    }
+
+    /// Returns a block; the cave is restored once every block is back.
+    fn free_autobot_bytes(&mut self, address: usize) -> Result<()> {
+        let index = self.autobot_blocks().iter()
+            .position(|block| *block == address)
+            .ok_or(WizWalkerError::FreeFailed { address })?;
+
+        self.autobot_blocks().swap_remove(index);
+
+        if self.autobot_blocks().is_empty() {
+            self.restore_autobot()?;
+        }
+
+        Ok(())
+    }
+
+    /// Waits until the hook export at `export_address` holds a non-null pointer and returns it.
+    fn wait_for_hook_value(&mut self, export_address: usize, interval: Duration, timeout: Duration) -> Result<usize>
+    where
+        Self: Sized,
+    {
+        wait_for_export(self, export_address, interval, timeout)
+    }
+
+    /// Writes the original autobot bytes and protection back, invalidating every block still handed out.
+    fn restore_autobot(&mut self) -> Result<()> {
+        let address = match *self.autobot_address() {
+            Some(address) => address,
+            None => return Ok(())
+        };
+
+        let original_bytes = self.original_autobot_bytes().clone();
+        self.write_bytes_guarded(address, original_bytes)?;
+        unregister_emergency_restore(self.process(), address);
+
+        let old_protections = std::mem::take(self.original_autobot_protections());
+        self.original_autobot_bytes().clear();
+
+        *self.autobot_address() = None;
+        *self.autobot_pos() = 0;
+        self.autobot_blocks().clear();
+
+        restore_protections(self, &old_protections)
+    }
+}
+
+
+pub struct HookHandler {
+    process: HANDLE,
+    symbol_table: HashMap<String, HashMap<String, u32>>,
+    autobot_address: Option<usize>,
+    original_autobot_bytes: Vec<u8>,
+    original_autobot_protections: Vec<(usize, u32)>,
+    autobot_pos: usize,
+    autobot_blocks: Vec<usize>,
+}
+
+impl HookHandler {
+    pub fn new(process: HANDLE) -> Self {
+        Self {
+            process,
+            symbol_table: HashMap::new(),
+            autobot_address: None,
+            original_autobot_bytes: Vec::new(),
+            original_autobot_protections: Vec::new(),
+            autobot_pos: 0,
+            autobot_blocks: Vec::new(),
+        }
+    }
 }
 
-// TODO: THIS ENTIRE FUCKING FILE
\ No newline at end of file
+// The process handle is only passed to thread safe Win32 calls.
+unsafe impl Send for HookHandler {}
+
+impl WizWalkerMemoryReader for HookHandler {
+    fn process(&self) -> HANDLE {
+        self.process
+    }
+
+    fn symbol_table(&mut self) -> &mut HashMap<String, HashMap<String, u32>> {
+        &mut self.symbol_table
+    }
+}
+
+impl WizWalkerHookHandler for HookHandler {
+    fn autobot_address(&mut self) -> &mut Option<usize> {
+        &mut self.autobot_address
+    }
+    fn original_autobot_bytes(&mut self) -> &mut Vec<u8> {
+        &mut self.original_autobot_bytes
+    }
+    fn original_autobot_protections(&mut self) -> &mut Vec<(usize, u32)> {
+        &mut self.original_autobot_protections
+    }
+    fn autobot_pos(&mut self) -> &mut usize {
+        &mut self.autobot_pos
+    }
+    fn autobot_blocks(&mut self) -> &mut Vec<usize> {
+        &mut self.autobot_blocks
+    }
+}
+
+/// Lets hooks share the cave through `Arc<Mutex<HookHandler>>`; it lives in the client module, so it is always near.
+impl HookAllocator for HookHandler {
+    fn alloc_block(&mut self, _near: usize, size: usize) -> Result<usize> {
+        self.allocate_autobot_bytes(size)
+    }
+
+    fn free_block(&mut self, address: usize) -> Result<()> {
+        self.free_autobot_bytes(address)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::fake_memory::{FakeMemory, FAKE_MODULE_BASE};
+    use crate::memory::memory_reader::delegate_memory_reader;
+    use crate::memory::registry::emergency_restore_for;
+    use crate::memory::signatures::CLIENT_MODULE;
+    use winapi::um::winnt::PAGE_EXECUTE_READ;
+
+    const AUTOBOT_OFFSET: usize = 0x800;
+
+    /// Bytes matching the autobot signature, padded with `int3`.
+    const AUTOBOT_CODE: [u8; 53] = [
+        0x48, 0x8B, 0xC4, 0x55, 0x41, 0x54, 0x41, 0x55, 0x41, 0x56, 0x41, 0x57,
+        0x48, 0x00, 0x00, 0x00, 0x48, 0x00, 0x00, 0x00,
+        0x48, 0x89, 0x58, 0x10, 0x48, 0x89, 0x70, 0x18, 0x48, 0x89, 0x78, 0x20,
+        0x48, 0x33, 0xC4, 0x00, 0x00, 0x00, 0x4C, 0x8B, 0xE9,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x0F,
+    ];
+
+    struct FakeHookHandler {
+        memory: FakeMemory,
+        autobot_address: Option<usize>,
+        original_autobot_bytes: Vec<u8>,
+        original_autobot_protections: Vec<(usize, u32)>,
+        autobot_pos: usize,
+        autobot_blocks: Vec<usize>,
+    }
+
+    impl WizWalkerMemoryReader for FakeHookHandler {
+        delegate_memory_reader!(memory);
+    }
+
+    impl WizWalkerHookHandler for FakeHookHandler {
+        fn autobot_address(&mut self) -> &mut Option<usize> {
+            &mut self.autobot_address
+        }
+        fn original_autobot_bytes(&mut self) -> &mut Vec<u8> {
+            &mut self.original_autobot_bytes
+        }
+        fn original_autobot_protections(&mut self) -> &mut Vec<(usize, u32)> {
+            &mut self.original_autobot_protections
+        }
+        fn autobot_pos(&mut self) -> &mut usize {
+            &mut self.autobot_pos
+        }
+        fn autobot_blocks(&mut self) -> &mut Vec<usize> {
+            &mut self.autobot_blocks
+        }
+    }
+
+    /// A handler over a read-execute client module at `module_base`.
+    ///
+    /// Emergency restores are global, so each test uses its own base.
+    fn hook_handler(module_base: usize) -> (FakeMemory, FakeHookHandler, Vec<u8>) {
+        let mut memory = FakeMemory::new();
+
+        let mut module = vec![0xCC; 0x2000];
+        module[AUTOBOT_OFFSET..AUTOBOT_OFFSET + AUTOBOT_CODE.len()].copy_from_slice(&AUTOBOT_CODE);
+        memory.map_module(CLIENT_MODULE, module_base, module.clone());
+        memory.protect(module_base, module.len(), PAGE_EXECUTE_READ).unwrap();
+
+        let handler = FakeHookHandler {
+            memory: memory.clone(),
+            autobot_address: None,
+            original_autobot_bytes: Vec::new(),
+            original_autobot_protections: Vec::new(),
+            autobot_pos: 0,
+            autobot_blocks: Vec::new(),
+        };
+
+        (memory, handler, module[AUTOBOT_OFFSET..AUTOBOT_OFFSET + AUTOBOT_SIZE].to_vec())
+    }
+
+    #[test]
+    fn taking_over_the_cave_disables_it_and_makes_it_writable() {
+        let module_base = FAKE_MODULE_BASE;
+        let (mut memory, mut handler, original) = hook_handler(module_base);
+        let autobot_address = module_base + AUTOBOT_OFFSET;
+
+        assert_eq!(handler.get_autobot_address().unwrap(), autobot_address);
+        assert_eq!(memory.read_bytes(autobot_address, 1).unwrap(), [0xC3]);
+        assert_eq!(emergency_restore_for(memory.process(), autobot_address), Some(original));
+
+        for address in (autobot_address..autobot_address + AUTOBOT_SIZE).step_by(PAGE_SIZE) {
+            assert_eq!(memory.protection(address), PAGE_EXECUTE_READWRITE);
+        }
+
+        handler.restore_autobot().unwrap();
+    }
+
+    #[test]
+    fn blocks_are_zeroed_and_handed_out_after_the_reserved_ret() {
+        let module_base = FAKE_MODULE_BASE + 0x10_0000;
+        let (mut memory, mut handler, _) = hook_handler(module_base);
+        let autobot_address = module_base + AUTOBOT_OFFSET;
+
+        let first = handler.allocate_autobot_bytes(8).unwrap();
+        let second = handler.allocate_autobot_bytes(BLOCK_ALIGNMENT + 1).unwrap();
+
+        assert_eq!(first, autobot_address + AUTOBOT_RESERVED);
+        assert_eq!(second, first + BLOCK_ALIGNMENT);
+        assert_eq!(memory.read_bytes(second, 2 * BLOCK_ALIGNMENT).unwrap(), vec![0; 2 * BLOCK_ALIGNMENT]);
+
+        // Hook exports live in these blocks, so they must stay writable.
+        memory.write_typed(first, 0x1234_usize).unwrap();
+        assert_eq!(memory.protection(first), PAGE_EXECUTE_READWRITE);
+
+        assert!(matches!(handler.allocate_autobot_bytes(AUTOBOT_SIZE), Err(WizWalkerError::HookTooLarge { .. })));
+
+        handler.restore_autobot().unwrap();
+    }
+
+    #[test]
+    fn freeing_the_last_block_restores_the_cave() {
+        let module_base = FAKE_MODULE_BASE + 0x20_0000;
+        let (mut memory, mut handler, original) = hook_handler(module_base);
+        let autobot_address = module_base + AUTOBOT_OFFSET;
+
+        let first = handler.allocate_autobot_bytes(8).unwrap();
+        let second = handler.allocate_autobot_bytes(8).unwrap();
+
+        handler.free_autobot_bytes(first).unwrap();
+        assert!(matches!(handler.free_autobot_bytes(first), Err(WizWalkerError::FreeFailed { .. })));
+        assert_eq!(memory.read_bytes(autobot_address, 1).unwrap(), [0xC3]);
+
+        handler.free_autobot_bytes(second).unwrap();
+        assert_eq!(memory.read_bytes(autobot_address, AUTOBOT_SIZE).unwrap(), original);
+        assert_eq!(memory.protection(autobot_address), PAGE_EXECUTE_READ);
+        assert_eq!(memory.protection(autobot_address + AUTOBOT_SIZE - 1), PAGE_EXECUTE_READ);
+        assert_eq!(emergency_restore_for(memory.process(), autobot_address), None);
+
+        assert_eq!(handler.allocate_autobot_bytes(8).unwrap(), first);
+        handler.restore_autobot().unwrap();
+    }
+}
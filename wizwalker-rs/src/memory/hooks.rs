@@ -1,17 +1,37 @@
-use anyhow::{Result, anyhow};
+use crate::error::{Result, WizWalkerError};
+use iced_x86::code_asm::{al, byte_ptr, dword_ptr, eax, ecx, qword_ptr, r14, r15, rax, rbx, rcx, CodeAssembler};
 // use wizwalker_macros::memory_hook;
 
 
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use winapi::um::winnt::HANDLE;
 
-use super::memory_reader::WizWalkerMemoryReader;
+use super::assembler::{
+    assembled_size, jump, pad_with_nops, steal_instructions, HookCodeBuilder, StolenInstructions, BRANCH_SCAN_WINDOW,
+    HOOK_SIZE_SLACK, MAX_INSTRUCTION_SIZE,
+};
+use super::allocator::{shared_pool, ExecutablePool, HookAllocator};
+use super::memory_reader::{delegate_memory_reader, MemoryReader, WizWalkerMemoryReader};
+use super::registry::HookInfo;
+use super::memory_objects::actor_body::ActorBody;
+use super::memory_objects::duel::Duel;
+use super::memory_objects::render_context::RenderContext;
+use super::memory_objects::window::Window;
+use super::signatures::{
+    CLIENT_HOOK, DUEL_HOOK, MOVEMENT_TELEPORT_HOOK, PLAYER_HOOK, QUEST_HOOK, RENDER_CONTEXT_HOOK, ROOT_WINDOW_HOOK,
+};
+use crate::utils::XYZ;
 
 
 
-pub struct MemoryHook {
-    process: HANDLE,
-    symbol_table: HashMap<String, HashMap<String, u32>>,
+/// The state every hook needs, over any memory backend.
+///
+/// Concrete hooks wrap one and forward to it with `delegate_memory_reader!`
+/// and `delegate_hook_state!`, so they only implement what makes them different.
+pub struct MemoryHook<R: WizWalkerMemoryReader = MemoryReader> {
+    memory: R,
     hook_handler: HANDLE,
     hook_cache: HashMap<String, usize>,
     jump_original_bytecode: Vec<u8>,
@@ -20,14 +40,32 @@ pub struct MemoryHook {
     jump_address: usize,
     hook_bytecode: Vec<u8>,
     allocated_addresses: Vec<usize>,
+    allocator: Arc<Mutex<dyn HookAllocator>>,
 }
 
-impl MemoryHook {
+impl MemoryHook<MemoryReader> {
+    /// Allocates from the executable pool shared by every hook on the process.
     pub fn new(hook_handler: HANDLE) -> Self {
+        Self::with_allocator(hook_handler, shared_pool(hook_handler))
+    }
+
+    /// Allocates from a pool of its own, e.g. to keep a hook's code apart from the others.
+    pub fn with_private_pool(hook_handler: HANDLE) -> Self {
+        Self::with_allocator(hook_handler, Arc::new(Mutex::new(ExecutablePool::new(hook_handler))))
+    }
+
+    /// Shares `allocator` with other hooks so their code lands in the same regions.
+    pub fn with_allocator(hook_handler: HANDLE, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
+        Self::with_memory(MemoryReader::new(hook_handler), allocator)
+    }
+}
+
+impl<R: WizWalkerMemoryReader> MemoryHook<R> {
+    /// Builds a hook reading and writing through `memory`, e.g. a fake backend in tests.
+    pub fn with_memory(memory: R, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
         Self {
-            process: hook_handler,
-            symbol_table: HashMap::new(),
-            hook_handler: hook_handler,
+            hook_handler: memory.process(),
+            memory,
             hook_cache: HashMap::new(),
             jump_original_bytecode: Vec::new(),
             jump_bytecode: Vec::new(),
@@ -35,22 +73,17 @@ impl MemoryHook {
             jump_address: 0x0,
             hook_bytecode: Vec::new(),
             allocated_addresses: Vec::new(),
+            allocator,
         }
     }
 }
 
-impl WizWalkerMemoryReader for MemoryHook {
-    fn process(&self) -> HANDLE {
-        self.process
-    }
-
-    fn symbol_table(&mut self) -> &mut HashMap<String, HashMap<String, u32>> {
-        &mut self.symbol_table
-    }
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for MemoryHook<R> {
+    delegate_memory_reader!(memory);
 }
 
 
-impl WizWalkerMemoryHook for MemoryHook {
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryHook for MemoryHook<R> {
     fn hook_handler(&self) -> HANDLE {
         self.hook_handler
     }
@@ -75,6 +108,43 @@ impl WizWalkerMemoryHook for MemoryHook {
     fn allocated_addresses(&mut self) -> &mut Vec<usize> {
         &mut self.allocated_addresses
     }
+    fn allocator(&self) -> Arc<Mutex<dyn HookAllocator>> {
+        self.allocator.clone()
+    }
+}
+
+
+/// Implements the `WizWalkerMemoryHook` state accessors by forwarding them to the `MemoryHook` in `self.$field`.
+macro_rules! delegate_hook_state {
+    ($field:ident) => {
+        fn hook_handler(&self) -> HANDLE {
+            self.$field.hook_handler()
+        }
+        fn hook_cache(&mut self) -> &mut HashMap<String, usize> {
+            self.$field.hook_cache()
+        }
+        fn jump_original_bytecode(&mut self) -> &mut Vec<u8> {
+            self.$field.jump_original_bytecode()
+        }
+        fn jump_bytecode(&mut self) -> &mut Vec<u8> {
+            self.$field.jump_bytecode()
+        }
+        fn hook_address(&mut self) -> &mut usize {
+            self.$field.hook_address()
+        }
+        fn jump_address(&mut self) -> &mut usize {
+            self.$field.jump_address()
+        }
+        fn hook_bytecode(&mut self) -> &mut Vec<u8> {
+            self.$field.hook_bytecode()
+        }
+        fn allocated_addresses(&mut self) -> &mut Vec<usize> {
+            self.$field.allocated_addresses()
+        }
+        fn allocator(&self) -> Arc<Mutex<dyn HookAllocator>> {
+            self.$field.allocator()
+        }
+    };
 }
 
 
@@ -87,6 +157,7 @@ pub trait WizWalkerMemoryHook: WizWalkerMemoryReader {
     fn jump_address(&mut self) -> &mut usize;
     fn hook_bytecode(&mut self) -> &mut Vec<u8>;
     fn allocated_addresses(&mut self) -> &mut Vec<usize>;
+    fn allocator(&self) -> Arc<Mutex<dyn HookAllocator>>;
 
     fn is_cached(&mut self, name: &str) -> bool {
         return self.hook_cache().contains_key(name)
@@ -101,71 +172,132 @@ pub trait WizWalkerMemoryHook: WizWalkerMemoryReader {
         return self.hook_cache().get(name).copied()
     }
 
+    /// Allocates executable memory within `rel32` range of the jump address, once it is known.
     fn alloc(&mut self, size: usize) -> Result<usize> {
-        let addr = self.allocate(size)?;
+        let near = *self.jump_address();
+        let addr = self.allocator().lock()?.alloc_block(near, size)?;
         self.allocated_addresses().push(addr);
         return Ok(addr)
     }
 
-    fn prehook(&self) {
-        unimplemented!()
+    /// Runs before the hook is written, e.g. to allocate export slots used by the body.
+    fn prehook(&mut self) -> Result<()> {
+        Ok(())
     }
 
-    fn posthook(&self) {
-        unimplemented!()
+    /// Runs after the hook is written.
+    fn posthook(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Runs after the hook is removed and its allocations are freed.
+    fn postunhook(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Emits the hook's own code.
+    ///
+    /// The instructions stolen from the jump site and the jump back are appended
+    /// automatically by `get_hook_bytecode`.
+    fn hook_body(&mut self, _asm: &mut CodeAssembler) -> Result<()> {
+        Ok(())
     }
 
     fn get_jump_address(&mut self, pattern: &str, module: Option<&str>) -> Result<usize> {
         let jump_addresses = self.pattern_scan(pattern, module, false)?;
         return match jump_addresses.first() {
             Some(addr) => Ok(*addr),
-            None => return Err(anyhow!("Could not find jump address. Pattern \"{pattern}\"."))
+            None => return Err(WizWalkerError::PatternNotFound { pattern: pattern.to_string() })
         }
     }
 
     fn get_hook_address(&mut self, size: usize) -> Result<usize> {
-        return self.allocate(size)
+        self.alloc(size)
     }
 
-    fn get_jump_bytecode(&self) -> Result<Vec<u8>> {
-        unimplemented!()
+    /// Bytes to allocate for the hook, measured from the body plus room for the stolen instructions.
+    fn get_hook_size(&mut self) -> Result<usize> {
+        let mut asm = CodeAssembler::new(64)?;
+        self.hook_body(&mut asm)?;
+
+        let jump_addr = *self.jump_address();
+        Ok(assembled_size(&mut asm, jump_addr)? + HOOK_SIZE_SLACK)
     }
 
-    fn get_hook_bytecode(&self) -> Result<Vec<u8>> {
-        unimplemented!()
+    fn get_jump_bytecode(&mut self) -> Result<Vec<u8>> {
+        let jump_addr = *self.jump_address();
+        let hook_addr = *self.hook_address();
+
+        Ok(jump(jump_addr, hook_addr))
+    }
+
+    fn get_hook_bytecode(&mut self) -> Result<Vec<u8>> {
+        let jump_addr = *self.jump_address();
+        let hook_addr = *self.hook_address();
+        let stolen_bytec = self.jump_original_bytecode().clone();
+
+        let mut builder = HookCodeBuilder::new(hook_addr)?.stolen(&stolen_bytec, jump_addr);
+        self.hook_body(builder.body())?;
+
+        builder.build()
+    }
+
+    /// Decodes the hook site and picks the instructions the jump will displace.
+    fn get_stolen_instructions(&mut self, jump_addr: usize, jump_size: usize) -> Result<StolenInstructions> {
+        let minimum_size = jump_size + MAX_INSTRUCTION_SIZE;
+
+        // The scan window may run off the end of the mapped region; the instructions themselves may not.
+        let site_code = match self.read_bytes(jump_addr, minimum_size + BRANCH_SCAN_WINDOW) {
+            Ok(code) => code,
+            Err(_) => self.read_bytes(jump_addr, minimum_size)?
+        };
+
+        steal_instructions(&site_code, jump_addr, jump_size)
     }
 
     fn get_pattern(&self) -> Result<(String, String)> {
         unimplemented!()
     }
 
+    /// The pointer export the hook fills once the game runs the hooked code, while hooked.
+    ///
+    /// Only pointer exports belong here, since waiting treats any non-zero value as ready.
+    fn export_address(&mut self) -> Option<usize> {
+        None
+    }
+
     fn hook(&mut self) -> Result<()> {
         let (pattern, module) = self.get_pattern()?;
         
         *self.jump_address() = self.get_jump_address(&pattern, Some(&module))?;
-        *self.hook_address() = self.get_hook_address(50)?;
-    
-        *self.hook_bytecode() = self.get_hook_bytecode()?;
-        *self.jump_bytecode() = self.get_jump_bytecode()?;
+
+        self.prehook()?;
+
+        let hook_size = self.get_hook_size()?;
+        *self.hook_address() = self.get_hook_address(hook_size)?;
 
         let jump_addr = *self.jump_address();
-        let jump_b_size = self.jump_bytecode().len();
-     
-        *self.jump_original_bytecode() = self.read_bytes(
-            jump_addr, 
-        jump_b_size,
-        )?;
-    
-        self.prehook();
+        let jump_bytec = self.get_jump_bytecode()?;
+
+        // Steal whole instructions only; the rest of the last one becomes nops.
+        let stolen = self.get_stolen_instructions(jump_addr, jump_bytec.len())?;
+        *self.jump_bytecode() = pad_with_nops(jump_bytec, stolen.len());
+        *self.jump_original_bytecode() = stolen.bytecode;
+
+        *self.hook_bytecode() = self.get_hook_bytecode()?;
 
         let hook_addr = *self.hook_address();
         let jump_bytec = self.jump_bytecode().clone();
         let hook_bytec = self.hook_bytecode().clone();
+
+        if hook_bytec.len() > hook_size {
+            return Err(WizWalkerError::HookTooLarge { size: hook_bytec.len(), allocated: hook_size })
+        }
     
-        self.write_bytes(hook_addr, hook_bytec)?;
-        self.write_bytes(jump_addr, jump_bytec)?;
+        self.write_bytes_guarded(hook_addr, hook_bytec)?;
+        self.write_bytes_guarded(jump_addr, jump_bytec)?;
 
-        self.posthook();
+        self.posthook()?;
     
         Ok(())
     }
@@ -175,12 +307,691 @@ pub trait WizWalkerMemoryHook: WizWalkerMemoryReader {
         let jump_original_bytec = self.jump_original_bytecode().clone();
         let allocated_addrs = self.allocated_addresses().clone();
 
-        self.write_bytes(jump_addr, jump_original_bytec)?;
+        if !jump_original_bytec.is_empty() {
+            let jump_bytec = self.jump_bytecode().clone();
+            let current_bytec = self.read_bytes(jump_addr, jump_bytec.len())?;
+
+            if current_bytec != jump_bytec {
+                log::warn!("Hook site \"{:#x}\" was modified after hooking; restoring original bytes anyway.", jump_addr);
+            }
+
+            self.write_bytes_guarded(jump_addr, jump_original_bytec)?;
+            self.jump_original_bytecode().clear();
+        }
         
+        let allocator = self.allocator();
         for addr in allocated_addrs {
-            self.free(addr)?;
+            allocator.lock()?.free_block(addr)?;
+        }
+
+        self.allocated_addresses().clear();
+
+        self.postunhook()
+    }
+}
+
+
+
+/// Polls the pointer at `export_address` every `interval` until it is non-null.
+///
+/// Exports stay zero until the game first runs the hooked code, e.g. the
+/// duel export until a duel starts. Fails with `HookTimeout` after `timeout`.
+pub fn wait_for_export<R: WizWalkerMemoryReader>(reader: &mut R, export_address: usize, interval: Duration, timeout: Duration) -> Result<usize> {
+    let started = Instant::now();
+
+    loop {
+        let value: usize = reader.read_typed(export_address)?;
+        if value != 0 {
+            return Ok(value)
+        }
+
+        let elapsed = started.elapsed();
+        if elapsed >= timeout {
+            return Err(WizWalkerError::HookTimeout { address: export_address, timeout })
+        }
+
+        std::thread::sleep(interval.min(timeout - elapsed));
+    }
+}
+
+
+/// Captures the local player's actor body as the game reads its position.
+///
+/// The hooked code reads the body's position at `+0x58`, so `rax` is an
+/// `ActorBody`, not a client object. The body compares the object type at
+/// `+0x474` against the player's and, on a match, stores `rax` in an 8 byte
+/// export read by `player_base`.
+pub struct PlayerHook<R: WizWalkerMemoryReader = MemoryReader> {
+    base: MemoryHook<R>,
+    player_struct: usize,
+}
+
+impl PlayerHook<MemoryReader> {
+    pub fn new(hook_handler: HANDLE, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
+        Self::with_hook(MemoryHook::with_allocator(hook_handler, allocator))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> PlayerHook<R> {
+    pub fn with_hook(base: MemoryHook<R>) -> Self {
+        Self {
+            base,
+            player_struct: 0x0,
+        }
+    }
+
+    /// Address of the export holding the player's actor body, or 0 when not hooked.
+    pub fn player_struct(&self) -> usize {
+        self.player_struct
+    }
+
+    /// The local player's actor body, once the game has run the hooked code.
+    pub fn player_base(&mut self) -> Result<usize> {
+        if self.player_struct == 0 {
+            return Err(WizWalkerError::HookNotActive { name: "PlayerHook".to_string() })
+        }
+
+        let player_base: usize = self.read_typed(self.player_struct)?;
+        if player_base == 0 {
+            return Err(WizWalkerError::NullPointer { address: self.player_struct })
+        }
+
+        Ok(player_base)
+    }
+
+    /// The local player's actor body, to turn or move it.
+    pub fn body(&mut self) -> Result<ActorBody> {
+        Ok(ActorBody::new(self.player_base()?))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> HookInfo for PlayerHook<R> {
+    const NAME: &'static str = "PlayerHook";
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for PlayerHook<R> {
+    delegate_memory_reader!(base);
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryHook for PlayerHook<R> {
+    delegate_hook_state!(base);
+
+    fn get_pattern(&self) -> Result<(String, String)> {
+        Ok(PLAYER_HOOK.to_owned_pair())
+    }
+
+    fn export_address(&mut self) -> Option<usize> {
+        match self.player_struct {
+            0 => None,
+            address => Some(address)
+        }
+    }
+
+    fn prehook(&mut self) -> Result<()> {
+        self.player_struct = self.alloc(8)?;
+        Ok(())
+    }
+
+    fn hook_body(&mut self, asm: &mut CodeAssembler) -> Result<()> {
+        let mut skip = asm.create_label();
+
+        asm.push(rcx)?;
+        asm.mov(ecx, dword_ptr(rax + 0x474))?;
+        asm.cmp(ecx, 8)?;
+        asm.pop(rcx)?;
+        asm.jne(skip)?;
+        asm.mov(qword_ptr(self.player_struct as u64), rax)?;
+        asm.set_label(&mut skip)?;
+        asm.nop()?;
+
+        Ok(())
+    }
+
+    fn postunhook(&mut self) -> Result<()> {
+        self.player_struct = 0x0;
+        Ok(())
+    }
+}
+
+
+
+/// Offset of the current client object inside the game client.
+const GAME_CLIENT_CLIENT_OBJECT_OFFSET: i32 = 0x1B8;
+
+/// Captures the local player's client object as the game client loads it.
+///
+/// The body does the game's load itself and stores the result in an 8 byte
+/// export, before the stolen instructions run.
+pub struct ClientHook<R: WizWalkerMemoryReader = MemoryReader> {
+    base: MemoryHook<R>,
+    current_client_addr: usize,
+}
+
+impl ClientHook<MemoryReader> {
+    pub fn new(hook_handler: HANDLE, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
+        Self::with_hook(MemoryHook::with_allocator(hook_handler, allocator))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> ClientHook<R> {
+    pub fn with_hook(base: MemoryHook<R>) -> Self {
+        Self {
+            base,
+            current_client_addr: 0x0,
+        }
+    }
+
+    /// Address of the export holding the player's client object, or 0 when not hooked.
+    pub fn current_client_addr(&self) -> usize {
+        self.current_client_addr
+    }
+
+    /// The local player's client object, once the game has run the hooked code.
+    pub fn client_base(&mut self) -> Result<usize> {
+        if self.current_client_addr == 0 {
+            return Err(WizWalkerError::HookNotActive { name: "ClientHook".to_string() })
+        }
+
+        let client_base: usize = self.read_typed(self.current_client_addr)?;
+        if client_base == 0 {
+            return Err(WizWalkerError::NullPointer { address: self.current_client_addr })
+        }
+
+        Ok(client_base)
+    }
+}
+
+impl<R: WizWalkerMemoryReader> HookInfo for ClientHook<R> {
+    const NAME: &'static str = "ClientHook";
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for ClientHook<R> {
+    delegate_memory_reader!(base);
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryHook for ClientHook<R> {
+    delegate_hook_state!(base);
+
+    fn get_pattern(&self) -> Result<(String, String)> {
+        Ok(CLIENT_HOOK.to_owned_pair())
+    }
+
+    fn export_address(&mut self) -> Option<usize> {
+        match self.current_client_addr {
+            0 => None,
+            address => Some(address)
+        }
+    }
+
+    fn prehook(&mut self) -> Result<()> {
+        self.current_client_addr = self.alloc(8)?;
+        Ok(())
+    }
+
+    fn hook_body(&mut self, asm: &mut CodeAssembler) -> Result<()> {
+        // Only `rax` can be stored to a 64 bit absolute address.
+        asm.push(rax)?;
+        asm.mov(rax, qword_ptr(rax + GAME_CLIENT_CLIENT_OBJECT_OFFSET))?;
+        asm.mov(qword_ptr(self.current_client_addr as u64), rax)?;
+        asm.pop(rax)?;
+
+        Ok(())
+    }
+
+    fn postunhook(&mut self) -> Result<()> {
+        self.current_client_addr = 0x0;
+        Ok(())
+    }
+}
+
+
+
+/// Captures the duel the client is currently updating.
+///
+/// Hooks the entry of the duel update, where `rcx` holds the `Duel`, and
+/// stores it in an 8 byte export. The pointer keeps its last value once the
+/// duel ends, so check `Duel::is_active` before trusting it.
+pub struct DuelHook<R: WizWalkerMemoryReader = MemoryReader> {
+    base: MemoryHook<R>,
+    current_duel_addr: usize,
+}
+
+impl DuelHook<MemoryReader> {
+    pub fn new(hook_handler: HANDLE, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
+        Self::with_hook(MemoryHook::with_allocator(hook_handler, allocator))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> DuelHook<R> {
+    pub fn with_hook(base: MemoryHook<R>) -> Self {
+        Self {
+            base,
+            current_duel_addr: 0x0,
+        }
+    }
+
+    /// Address of the export holding the current duel, or 0 when not hooked.
+    pub fn current_duel_addr(&self) -> usize {
+        self.current_duel_addr
+    }
+
+    /// The last duel the client updated.
+    pub fn current_duel(&mut self) -> Result<Duel> {
+        if self.current_duel_addr == 0 {
+            return Err(WizWalkerError::HookNotActive { name: "DuelHook".to_string() })
+        }
+
+        let duel_base: usize = self.read_typed(self.current_duel_addr)?;
+        if duel_base == 0 {
+            return Err(WizWalkerError::NullPointer { address: self.current_duel_addr })
+        }
+
+        Ok(Duel::new(duel_base))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> HookInfo for DuelHook<R> {
+    const NAME: &'static str = "DuelHook";
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for DuelHook<R> {
+    delegate_memory_reader!(base);
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryHook for DuelHook<R> {
+    delegate_hook_state!(base);
+
+    fn get_pattern(&self) -> Result<(String, String)> {
+        Ok(DUEL_HOOK.to_owned_pair())
+    }
+
+    fn export_address(&mut self) -> Option<usize> {
+        match self.current_duel_addr {
+            0 => None,
+            address => Some(address)
+        }
+    }
+
+    fn prehook(&mut self) -> Result<()> {
+        self.current_duel_addr = self.alloc(8)?;
+        Ok(())
+    }
+
+    fn hook_body(&mut self, asm: &mut CodeAssembler) -> Result<()> {
+        asm.push(rax)?;
+        asm.mov(rax, rcx)?;
+        asm.mov(qword_ptr(self.current_duel_addr as u64), rax)?;
+        asm.pop(rax)?;
+
+        Ok(())
+    }
+
+    fn postunhook(&mut self) -> Result<()> {
+        self.current_duel_addr = 0x0;
+        Ok(())
+    }
+}
+
+
+
+/// Offset of the active quest's position inside the quest client manager.
+const QUEST_POSITION_OFFSET: i32 = 0xCFC;
+
+/// Captures the position of the active quest as the quest client manager reads it.
+pub struct QuestHook<R: WizWalkerMemoryReader = MemoryReader> {
+    base: MemoryHook<R>,
+    cord_struct: usize,
+}
+
+impl QuestHook<MemoryReader> {
+    pub fn new(hook_handler: HANDLE, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
+        Self::with_hook(MemoryHook::with_allocator(hook_handler, allocator))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> QuestHook<R> {
+    pub fn with_hook(base: MemoryHook<R>) -> Self {
+        Self {
+            base,
+            cord_struct: 0x0,
+        }
+    }
+
+    /// Address of the export holding the quest position, or 0 when not hooked.
+    pub fn cord_struct(&self) -> usize {
+        self.cord_struct
+    }
+
+    /// Position of the active quest; all zeros until the client has read it once.
+    pub fn quest_position(&mut self) -> Result<XYZ> {
+        if self.cord_struct == 0 {
+            return Err(WizWalkerError::HookNotActive { name: "QuestHook".to_string() })
+        }
+
+        self.read_typed(self.cord_struct)
+    }
+}
+
+impl<R: WizWalkerMemoryReader> HookInfo for QuestHook<R> {
+    const NAME: &'static str = "QuestHook";
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for QuestHook<R> {
+    delegate_memory_reader!(base);
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryHook for QuestHook<R> {
+    delegate_hook_state!(base);
+
+    fn get_pattern(&self) -> Result<(String, String)> {
+        Ok(QUEST_HOOK.to_owned_pair())
+    }
+
+    // The export holds an `XYZ`, not a pointer, so it cannot be waited on.
+
+    fn prehook(&mut self) -> Result<()> {
+        self.cord_struct = self.alloc(std::mem::size_of::<XYZ>())?;
+        Ok(())
+    }
+
+    fn hook_body(&mut self, asm: &mut CodeAssembler) -> Result<()> {
+        let cord_struct = self.cord_struct as u64;
+
+        // x and y in one qword, then z.
+        asm.push(rax)?;
+        asm.mov(rax, qword_ptr(r14 + QUEST_POSITION_OFFSET))?;
+        asm.mov(qword_ptr(cord_struct), rax)?;
+        asm.mov(eax, dword_ptr(r14 + QUEST_POSITION_OFFSET + 8))?;
+        asm.mov(dword_ptr(cord_struct + 8), eax)?;
+        asm.pop(rax)?;
+
+        Ok(())
+    }
+
+    fn postunhook(&mut self) -> Result<()> {
+        self.cord_struct = 0x0;
+        Ok(())
+    }
+}
+
+
+/// Offset of the position `XYZ` inside an actor body.
+const ACTOR_BODY_POSITION_OFFSET: i32 = 0x58;
+
+// Layout of the teleport helper export: target position, pending flag, then the body to move.
+const TELEPORT_POSITION_OFFSET: usize = 0x0;
+const TELEPORT_SHOULD_UPDATE_OFFSET: usize = 0xC;
+const TELEPORT_TARGET_OFFSET: usize = 0x10;
+const TELEPORT_HELPER_SIZE: usize = 0x18;
+
+/// Moves an actor body by writing its position during the client's own movement update.
+///
+/// `teleport` fills the helper export and raises its flag last; the next
+/// update of the target body copies the position over and clears the flag,
+/// so the write never races the game's movement code.
+pub struct MovementTeleportHook<R: WizWalkerMemoryReader = MemoryReader> {
+    base: MemoryHook<R>,
+    teleport_helper: usize,
+}
+
+impl MovementTeleportHook<MemoryReader> {
+    pub fn new(hook_handler: HANDLE, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
+        Self::with_hook(MemoryHook::with_allocator(hook_handler, allocator))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> MovementTeleportHook<R> {
+    pub fn with_hook(base: MemoryHook<R>) -> Self {
+        Self {
+            base,
+            teleport_helper: 0x0,
+        }
+    }
+
+    /// Address of the teleport helper export, or 0 when not hooked.
+    pub fn teleport_helper(&self) -> usize {
+        self.teleport_helper
+    }
+
+    /// Queues a move of the actor body at `target_body` to `position`.
+    pub fn teleport(&mut self, target_body: usize, position: XYZ) -> Result<()> {
+        if self.teleport_helper == 0 {
+            return Err(WizWalkerError::HookNotActive { name: "MovementTeleportHook".to_string() })
+        }
+
+        let teleport_helper = self.teleport_helper;
+        self.write_typed(teleport_helper + TELEPORT_POSITION_OFFSET, position)?;
+        self.write_typed(teleport_helper + TELEPORT_TARGET_OFFSET, target_body)?;
+        self.write_typed(teleport_helper + TELEPORT_SHOULD_UPDATE_OFFSET, 1u8)
+    }
+
+    /// Whether a queued teleport has not been consumed by the client yet.
+    pub fn is_pending(&mut self) -> Result<bool> {
+        if self.teleport_helper == 0 {
+            return Ok(false)
+        }
+
+        Ok(self.read_typed::<u8>(self.teleport_helper + TELEPORT_SHOULD_UPDATE_OFFSET)? != 0)
+    }
+}
+
+impl<R: WizWalkerMemoryReader> HookInfo for MovementTeleportHook<R> {
+    const NAME: &'static str = "MovementTeleportHook";
+    const DEPENDENCIES: &'static [&'static str] = &["PlayerHook"];
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for MovementTeleportHook<R> {
+    delegate_memory_reader!(base);
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryHook for MovementTeleportHook<R> {
+    delegate_hook_state!(base);
+
+    fn get_pattern(&self) -> Result<(String, String)> {
+        Ok(MOVEMENT_TELEPORT_HOOK.to_owned_pair())
+    }
+
+    fn prehook(&mut self) -> Result<()> {
+        self.teleport_helper = self.alloc(TELEPORT_HELPER_SIZE)?;
+        Ok(())
+    }
+
+    fn hook_body(&mut self, asm: &mut CodeAssembler) -> Result<()> {
+        let teleport_helper = self.teleport_helper as u64;
+        let position = teleport_helper + TELEPORT_POSITION_OFFSET as u64;
+        let should_update = teleport_helper + TELEPORT_SHOULD_UPDATE_OFFSET as u64;
+        let target = teleport_helper + TELEPORT_TARGET_OFFSET as u64;
+        let mut done = asm.create_label();
+
+        asm.push(rax)?;
+        asm.mov(al, byte_ptr(should_update))?;
+        asm.test(al, al)?;
+        asm.jz(done)?;
+        asm.mov(rax, qword_ptr(target))?;
+        asm.cmp(rax, rcx)?;
+        asm.jne(done)?;
+
+        asm.mov(rax, qword_ptr(position))?;
+        asm.mov(qword_ptr(rcx + ACTOR_BODY_POSITION_OFFSET), rax)?;
+        asm.mov(eax, dword_ptr(position + 8))?;
+        asm.mov(dword_ptr(rcx + ACTOR_BODY_POSITION_OFFSET + 8), eax)?;
+
+        asm.xor(eax, eax)?;
+        asm.mov(byte_ptr(should_update), al)?;
+
+        asm.set_label(&mut done)?;
+        asm.pop(rax)?;
+
+        Ok(())
+    }
+
+    fn postunhook(&mut self) -> Result<()> {
+        self.teleport_helper = 0x0;
+        Ok(())
+    }
+}
+
+
+
+/// Captures the root of the UI window tree during the GUI frame update.
+pub struct RootWindowHook<R: WizWalkerMemoryReader = MemoryReader> {
+    base: MemoryHook<R>,
+    current_root_window_addr: usize,
+}
+
+impl RootWindowHook<MemoryReader> {
+    pub fn new(hook_handler: HANDLE, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
+        Self::with_hook(MemoryHook::with_allocator(hook_handler, allocator))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> RootWindowHook<R> {
+    pub fn with_hook(base: MemoryHook<R>) -> Self {
+        Self {
+            base,
+            current_root_window_addr: 0x0,
+        }
+    }
+
+    /// Address of the export holding the root window, or 0 when not hooked.
+    pub fn current_root_window_addr(&self) -> usize {
+        self.current_root_window_addr
+    }
+
+    pub fn root_window(&mut self) -> Result<Window> {
+        if self.current_root_window_addr == 0 {
+            return Err(WizWalkerError::HookNotActive { name: "RootWindowHook".to_string() })
+        }
+
+        let root_window: usize = self.read_typed(self.current_root_window_addr)?;
+        if root_window == 0 {
+            return Err(WizWalkerError::NullPointer { address: self.current_root_window_addr })
+        }
+
+        Ok(Window::new(root_window))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> HookInfo for RootWindowHook<R> {
+    const NAME: &'static str = "RootWindowHook";
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for RootWindowHook<R> {
+    delegate_memory_reader!(base);
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryHook for RootWindowHook<R> {
+    delegate_hook_state!(base);
+
+    fn get_pattern(&self) -> Result<(String, String)> {
+        Ok(ROOT_WINDOW_HOOK.to_owned_pair())
+    }
+
+    fn export_address(&mut self) -> Option<usize> {
+        match self.current_root_window_addr {
+            0 => None,
+            address => Some(address)
         }
+    }
 
+    fn prehook(&mut self) -> Result<()> {
+        self.current_root_window_addr = self.alloc(8)?;
+        Ok(())
+    }
+
+    fn hook_body(&mut self, asm: &mut CodeAssembler) -> Result<()> {
+        asm.push(rax)?;
+        asm.mov(rax, rbx)?;
+        asm.mov(qword_ptr(self.current_root_window_addr as u64), rax)?;
+        asm.pop(rax)?;
+
+        Ok(())
+    }
+
+    fn postunhook(&mut self) -> Result<()> {
+        self.current_root_window_addr = 0x0;
+        Ok(())
+    }
+}
+
+
+/// Captures the UI render context, which holds the UI scale.
+pub struct RenderContextHook<R: WizWalkerMemoryReader = MemoryReader> {
+    base: MemoryHook<R>,
+    current_render_context_addr: usize,
+}
+
+impl RenderContextHook<MemoryReader> {
+    pub fn new(hook_handler: HANDLE, allocator: Arc<Mutex<dyn HookAllocator>>) -> Self {
+        Self::with_hook(MemoryHook::with_allocator(hook_handler, allocator))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> RenderContextHook<R> {
+    pub fn with_hook(base: MemoryHook<R>) -> Self {
+        Self {
+            base,
+            current_render_context_addr: 0x0,
+        }
+    }
+
+    /// Address of the export holding the render context, or 0 when not hooked.
+    pub fn current_render_context_addr(&self) -> usize {
+        self.current_render_context_addr
+    }
+
+    pub fn render_context(&mut self) -> Result<RenderContext> {
+        if self.current_render_context_addr == 0 {
+            return Err(WizWalkerError::HookNotActive { name: "RenderContextHook".to_string() })
+        }
+
+        let render_context: usize = self.read_typed(self.current_render_context_addr)?;
+        if render_context == 0 {
+            return Err(WizWalkerError::NullPointer { address: self.current_render_context_addr })
+        }
+
+        Ok(RenderContext::new(render_context))
+    }
+}
+
+impl<R: WizWalkerMemoryReader> HookInfo for RenderContextHook<R> {
+    const NAME: &'static str = "RenderContextHook";
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for RenderContextHook<R> {
+    delegate_memory_reader!(base);
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryHook for RenderContextHook<R> {
+    delegate_hook_state!(base);
+
+    fn get_pattern(&self) -> Result<(String, String)> {
+        Ok(RENDER_CONTEXT_HOOK.to_owned_pair())
+    }
+
+    fn export_address(&mut self) -> Option<usize> {
+        match self.current_render_context_addr {
+            0 => None,
+            address => Some(address)
+        }
+    }
+
+    fn prehook(&mut self) -> Result<()> {
+        self.current_render_context_addr = self.alloc(8)?;
+        Ok(())
+    }
+
+    fn hook_body(&mut self, asm: &mut CodeAssembler) -> Result<()> {
+        asm.push(rax)?;
+        asm.mov(rax, qword_ptr(r15))?;
+        asm.mov(qword_ptr(self.current_render_context_addr as u64), rax)?;
+        asm.pop(rax)?;
+
+        Ok(())
+    }
+
+    fn postunhook(&mut self) -> Result<()> {
+        self.current_render_context_addr = 0x0;
         Ok(())
     }
 }
@@ -261,4 +1072,341 @@ pub trait WizWalkerMemoryHook: WizWalkerMemoryReader {
 //     fn alloc(&mut self, size: usize) -> Result<usize> {
 //         self.hook_handler().
 //     }
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::assembler::decode_instructions;
+    use crate::memory::fake_memory::{FakeMemory, FAKE_MODULE_BASE};
+    use crate::memory::memory_object::MemoryObject;
+    use crate::memory::signatures::CLIENT_MODULE;
+    use iced_x86::{Code, Instruction, OpKind, Register};
+
+    const SITE: usize = FAKE_MODULE_BASE + 0x100;
+
+    // movsd xmm0, [rax+0x58]; movsd xmm1, [rax+0x5C]; ret
+    const SITE_CODE: [u8; 11] = [0xF2, 0x0F, 0x10, 0x40, 0x58, 0xF2, 0x0F, 0x10, 0x48, 0x5C, 0xC3];
+
+    // The duel update's prologue, ending in mov r15, rcx; ret
+    const DUEL_SITE_CODE: [u8; 48] = [
+        0x48, 0x89, 0x5C, 0x24, 0x10, 0x48, 0x89, 0x74, 0x24, 0x18, 0x55, 0x57, 0x41, 0x54, 0x41, 0x56,
+        0x41, 0x57, 0x48, 0x8D, 0x6C, 0x24, 0xE9, 0x48, 0x81, 0xEC, 0x00, 0x01, 0x00, 0x00, 0x48, 0x8B,
+        0x05, 0x00, 0x10, 0x00, 0x00, 0x48, 0x33, 0xC4, 0x48, 0x89, 0x45, 0x08, 0x4C, 0x8B, 0xF9, 0xC3,
+    ];
+
+    // movss xmm0, [r14+0xCFC]; movss xmm1, [r14+0xD00]; ret
+    const QUEST_SITE_CODE: [u8; 19] = [
+        0xF3, 0x41, 0x0F, 0x10, 0x86, 0xFC, 0x0C, 0x00, 0x00, 0xF3, 0x41, 0x0F, 0x10, 0x8E, 0x00, 0x0D,
+        0x00, 0x00, 0xC3,
+    ];
+
+    // mov [rsp+8], rbx; push rdi; sub rsp, 0x20; mov rbx, [rcx+0xD0]; mov rdi, rcx; test rbx, rbx; jz ret; mov eax, [rbx+0x10]; ret
+    const MOVEMENT_SITE_CODE: [u8; 32] = [
+        0x48, 0x89, 0x5C, 0x24, 0x08, 0x57, 0x48, 0x83, 0xEC, 0x20, 0x48, 0x8B, 0x99, 0xD0, 0x00, 0x00,
+        0x00, 0x48, 0x8B, 0xF9, 0x48, 0x85, 0xDB, 0x74, 0x06, 0x8B, 0x83, 0x10, 0x00, 0x00, 0x00, 0xC3,
+    ];
+
+    // mov rax, [rcx]; mov rcx, rbx; call [rax+0x70]; test al, al; ret
+    const ROOT_WINDOW_SITE_CODE: [u8; 12] = [0x48, 0x8B, 0x01, 0x48, 0x8B, 0xCB, 0xFF, 0x50, 0x70, 0x84, 0xC0, 0xC3];
+
+    // movss xmm5, [r8]; movss xmm2, [rsi+4]; movsxd rax, ecx; ret
+    const RENDER_CONTEXT_SITE_CODE: [u8; 14] = [0xF3, 0x41, 0x0F, 0x10, 0x28, 0xF3, 0x0F, 0x10, 0x56, 0x04, 0x48, 0x63, 0xC1, 0xC3];
+
+    // mov rcx, [rax+0x1B8]; test rcx, rcx; jz ret; mov rax, [rcx]; call [rax+0x10]; ret
+    const CLIENT_SITE_CODE: [u8; 22] = [
+        0x48, 0x8B, 0x88, 0xB8, 0x01, 0x00, 0x00, 0x48, 0x85, 0xC9, 0x74, 0x09,
+        0x48, 0x8B, 0x01, 0xFF, 0x90, 0x10, 0x00, 0x00, 0x00, 0xC3,
+    ];
+
+    /// A client module with `site_code` at `SITE`, and a hook base over it.
+    fn fake_client(site_code: &[u8]) -> (FakeMemory, MemoryHook<FakeMemory>) {
+        let memory = FakeMemory::new();
+
+        let mut module = vec![0xCC; 0x1000];
+        module[0x100..0x100 + site_code.len()].copy_from_slice(site_code);
+        memory.map_module(CLIENT_MODULE, FAKE_MODULE_BASE, module);
+
+        let base = MemoryHook::with_memory(memory.clone(), Arc::new(Mutex::new(memory.clone())));
+        (memory, base)
+    }
+
+    fn player_hook() -> (FakeMemory, PlayerHook<FakeMemory>) {
+        let (memory, base) = fake_client(&SITE_CODE);
+        (memory, PlayerHook::with_hook(base))
+    }
+
+    fn hook_instructions<H: WizWalkerMemoryHook>(hook: &mut H) -> Vec<Instruction> {
+        let hook_addr = *hook.hook_address();
+        let hook_bytec = hook.hook_bytecode().clone();
+        decode_instructions(&hook_bytec, hook_addr).unwrap()
+    }
+
+    /// Whether the trampoline stores `register` to the absolute `address`.
+    fn stores_to(instructions: &[Instruction], address: usize, register: Register) -> bool {
+        instructions.iter().any(|instruction| {
+            instruction.op0_kind() == OpKind::Memory
+                && instruction.memory_base() == Register::None
+                && instruction.memory_displacement64() as usize == address
+                && instruction.op1_register() == register
+        })
+    }
+
+    /// Checks `SITE` jumps to the trampoline, which jumps back past the stolen instructions.
+    fn assert_hooked<H: WizWalkerMemoryHook>(memory: &mut FakeMemory, hook: &mut H) {
+        let hook_addr = *hook.hook_address();
+        let stolen = hook.jump_original_bytecode().len();
+
+        assert_eq!(*hook.jump_address(), SITE);
+        assert_eq!(memory.read_bytes(SITE, 5).unwrap(), jump(SITE, hook_addr));
+        assert_eq!(hook_instructions(hook).last().unwrap().near_branch_target() as usize, SITE + stolen);
+    }
+
+    /// Unhooks, checking the site is restored and every allocation freed.
+    fn assert_unhooks<H: WizWalkerMemoryHook>(memory: &mut FakeMemory, hook: &mut H, site_code: &[u8]) {
+        let allocations = hook.allocated_addresses().clone();
+        hook.unhook().unwrap();
+
+        assert_eq!(memory.read_bytes(SITE, site_code.len()).unwrap(), site_code);
+        assert!(allocations.iter().all(|address| !memory.is_mapped(*address)));
+        assert_eq!(hook.export_address(), None);
+    }
+
+    #[test]
+    fn player_hook_exports_the_player_object() {
+        let (mut memory, mut hook) = player_hook();
+        hook.hook().unwrap();
+
+        let hook_addr = *hook.hook_address();
+        let player_struct = hook.player_struct();
+        assert_eq!(*hook.jump_address(), SITE);
+        assert_eq!(memory.read_bytes(SITE, 5).unwrap(), jump(SITE, hook_addr));
+
+        let hook_bytec = hook.hook_bytecode().clone();
+        let instructions = decode_instructions(&hook_bytec, hook_addr).unwrap();
+
+        let export = instructions.iter().find(|instruction| instruction.code() == Code::Mov_moffs64_RAX).unwrap();
+        assert_eq!(export.memory_displacement64() as usize, player_struct);
+        assert_eq!(export.op1_register(), Register::RAX);
+
+        let return_jump = instructions.last().unwrap();
+        assert_eq!(return_jump.near_branch_target() as usize, SITE + 5);
+
+        assert!(matches!(hook.player_base(), Err(WizWalkerError::NullPointer { .. })));
+
+        memory.write_typed(player_struct, 0x2_0000_1000_usize).unwrap();
+        assert_eq!(hook.player_base().unwrap(), 0x2_0000_1000);
+    }
+
+    #[test]
+    fn player_hook_body_turns_the_exported_body() {
+        let (mut memory, mut hook) = player_hook();
+        hook.hook().unwrap();
+
+        let body_address = FAKE_MODULE_BASE + 0x800;
+        memory.write_typed(hook.player_struct(), body_address).unwrap();
+
+        let body = hook.body().unwrap();
+        assert_eq!(body.base_address(), body_address);
+
+        body.write_position(&mut memory, XYZ::new(0.0, 0.0, 0.0)).unwrap();
+        body.turn_towards(&mut memory, XYZ::new(100.0, 0.0, 0.0)).unwrap();
+        assert!(body.direction(&mut memory).unwrap().distance(&XYZ::new(1.0, 0.0, 0.0)) < 1e-4);
+    }
+
+    #[test]
+    fn player_hook_unhook_restores_the_site() {
+        let (mut memory, mut hook) = player_hook();
+        hook.hook().unwrap();
+
+        let hook_addr = *hook.hook_address();
+        let player_struct = hook.player_struct();
+        hook.unhook().unwrap();
+
+        assert_eq!(memory.read_bytes(SITE, SITE_CODE.len()).unwrap(), SITE_CODE);
+        assert!(!memory.is_mapped(hook_addr));
+        assert!(!memory.is_mapped(player_struct));
+        assert!(matches!(hook.player_base(), Err(WizWalkerError::HookNotActive { .. })));
+    }
+
+    #[test]
+    fn client_hook_exports_the_loaded_client_object() {
+        let (mut memory, base) = fake_client(&CLIENT_SITE_CODE);
+        let mut hook = ClientHook::with_hook(base);
+        hook.hook().unwrap();
+
+        let hook_addr = *hook.hook_address();
+        let current_client_addr = hook.current_client_addr();
+        assert_eq!(memory.read_bytes(SITE, 5).unwrap(), jump(SITE, hook_addr));
+
+        let hook_bytec = hook.hook_bytecode().clone();
+        let instructions = decode_instructions(&hook_bytec, hook_addr).unwrap();
+
+        let load = &instructions[1];
+        assert_eq!(load.code(), Code::Mov_r64_rm64);
+        assert_eq!(load.memory_base(), Register::RAX);
+        assert_eq!(load.memory_displacement64(), 0x1B8);
+
+        let export = &instructions[2];
+        assert_eq!(export.code(), Code::Mov_moffs64_RAX);
+        assert_eq!(export.memory_displacement64() as usize, current_client_addr);
+
+        assert!(matches!(hook.client_base(), Err(WizWalkerError::NullPointer { .. })));
+        memory.write_typed(current_client_addr, 0x2_0000_2000_usize).unwrap();
+        assert_eq!(hook.client_base().unwrap(), 0x2_0000_2000);
+
+        hook.unhook().unwrap();
+        assert_eq!(memory.read_bytes(SITE, CLIENT_SITE_CODE.len()).unwrap(), CLIENT_SITE_CODE);
+        assert!(matches!(hook.client_base(), Err(WizWalkerError::HookNotActive { .. })));
+    }
+
+    #[test]
+    fn duel_hook_exports_the_updated_duel() {
+        let (mut memory, base) = fake_client(&DUEL_SITE_CODE);
+        let mut hook = DuelHook::with_hook(base);
+        hook.hook().unwrap();
+        assert_hooked(&mut memory, &mut hook);
+
+        let current_duel_addr = hook.current_duel_addr();
+        assert_eq!(hook.export_address(), Some(current_duel_addr));
+
+        let instructions = hook_instructions(&mut hook);
+        assert!(instructions.iter().any(|instruction| {
+            instruction.op0_register() == Register::RAX && instruction.op1_register() == Register::RCX
+        }));
+        assert!(stores_to(&instructions, current_duel_addr, Register::RAX));
+
+        assert!(matches!(hook.current_duel(), Err(WizWalkerError::NullPointer { .. })));
+        memory.write_typed(current_duel_addr, 0x2_0000_3000_usize).unwrap();
+        assert_eq!(hook.current_duel().unwrap().base_address(), 0x2_0000_3000);
+
+        assert_unhooks(&mut memory, &mut hook, &DUEL_SITE_CODE);
+        assert!(matches!(hook.current_duel(), Err(WizWalkerError::HookNotActive { .. })));
+    }
+
+    #[test]
+    fn quest_hook_copies_the_quest_position() {
+        let (mut memory, base) = fake_client(&QUEST_SITE_CODE);
+        let mut hook = QuestHook::with_hook(base);
+        hook.hook().unwrap();
+        assert_hooked(&mut memory, &mut hook);
+
+        let cord_struct = hook.cord_struct();
+        assert_eq!(hook.export_address(), None);
+
+        let instructions = hook_instructions(&mut hook);
+        assert!(stores_to(&instructions, cord_struct, Register::RAX));
+        assert!(stores_to(&instructions, cord_struct + 8, Register::EAX));
+        assert!(instructions.iter().any(|instruction| {
+            instruction.memory_base() == Register::R14 && instruction.memory_displacement64() == QUEST_POSITION_OFFSET as u64 + 8
+        }));
+
+        assert_eq!(hook.quest_position().unwrap(), XYZ::new(0.0, 0.0, 0.0));
+        memory.write_typed(cord_struct, XYZ::new(1.0, 2.0, 3.0)).unwrap();
+        assert_eq!(hook.quest_position().unwrap(), XYZ::new(1.0, 2.0, 3.0));
+
+        assert_unhooks(&mut memory, &mut hook, &QUEST_SITE_CODE);
+        assert!(matches!(hook.quest_position(), Err(WizWalkerError::HookNotActive { .. })));
+    }
+
+    #[test]
+    fn movement_teleport_hook_writes_the_position_into_the_moving_body() {
+        let (mut memory, base) = fake_client(&MOVEMENT_SITE_CODE);
+        let mut hook = MovementTeleportHook::with_hook(base);
+        hook.hook().unwrap();
+        assert_hooked(&mut memory, &mut hook);
+
+        let instructions = hook_instructions(&mut hook);
+        for (displacement, register) in [(0x58, Register::RAX), (0x60, Register::EAX)] {
+            assert!(instructions.iter().any(|instruction| {
+                instruction.memory_base() == Register::RCX
+                    && instruction.memory_displacement64() == displacement
+                    && instruction.op1_register() == register
+            }));
+        }
+
+        assert_unhooks(&mut memory, &mut hook, &MOVEMENT_SITE_CODE);
+    }
+
+    #[test]
+    fn movement_teleport_hook_raises_the_flag_last() {
+        let (mut memory, base) = fake_client(&MOVEMENT_SITE_CODE);
+        let mut hook = MovementTeleportHook::with_hook(base);
+
+        assert!(matches!(hook.teleport(0x2_0000_4000, XYZ::new(1.0, 2.0, 3.0)), Err(WizWalkerError::HookNotActive { .. })));
+        assert!(!hook.is_pending().unwrap());
+
+        hook.hook().unwrap();
+        let teleport_helper = hook.teleport_helper();
+        assert!(!hook.is_pending().unwrap());
+
+        let writes_before = memory.writes().len();
+        hook.teleport(0x2_0000_4000, XYZ::new(1.0, 2.0, 3.0)).unwrap();
+
+        assert_eq!(memory.writes()[writes_before..], [
+            (teleport_helper + TELEPORT_POSITION_OFFSET, 12),
+            (teleport_helper + TELEPORT_TARGET_OFFSET, 8),
+            (teleport_helper + TELEPORT_SHOULD_UPDATE_OFFSET, 1),
+        ]);
+        assert_eq!(memory.read_typed::<XYZ>(teleport_helper + TELEPORT_POSITION_OFFSET).unwrap(), XYZ::new(1.0, 2.0, 3.0));
+        assert_eq!(memory.read_typed::<usize>(teleport_helper + TELEPORT_TARGET_OFFSET).unwrap(), 0x2_0000_4000);
+        assert!(hook.is_pending().unwrap());
+
+        // The game clears the flag once the body's next update copied the position.
+        memory.write_typed(teleport_helper + TELEPORT_SHOULD_UPDATE_OFFSET, 0u8).unwrap();
+        assert!(!hook.is_pending().unwrap());
+
+        hook.unhook().unwrap();
+        assert!(!hook.is_pending().unwrap());
+    }
+
+    #[test]
+    fn root_window_hook_exports_rbx() {
+        let (mut memory, base) = fake_client(&ROOT_WINDOW_SITE_CODE);
+        let mut hook = RootWindowHook::with_hook(base);
+        hook.hook().unwrap();
+        assert_hooked(&mut memory, &mut hook);
+
+        let root_window_addr = hook.current_root_window_addr();
+        assert_eq!(hook.export_address(), Some(root_window_addr));
+
+        let instructions = hook_instructions(&mut hook);
+        assert!(instructions.iter().any(|instruction| {
+            instruction.op0_register() == Register::RAX && instruction.op1_register() == Register::RBX
+        }));
+        assert!(stores_to(&instructions, root_window_addr, Register::RAX));
+
+        assert!(matches!(hook.root_window(), Err(WizWalkerError::NullPointer { .. })));
+        memory.write_typed(root_window_addr, 0x2_0000_5000_usize).unwrap();
+        assert_eq!(hook.root_window().unwrap().base_address(), 0x2_0000_5000);
+
+        assert_unhooks(&mut memory, &mut hook, &ROOT_WINDOW_SITE_CODE);
+        assert!(matches!(hook.root_window(), Err(WizWalkerError::HookNotActive { .. })));
+    }
+
+    #[test]
+    fn render_context_hook_exports_the_pointer_r15_points_at() {
+        let (mut memory, base) = fake_client(&RENDER_CONTEXT_SITE_CODE);
+        let mut hook = RenderContextHook::with_hook(base);
+        hook.hook().unwrap();
+        assert_hooked(&mut memory, &mut hook);
+
+        let render_context_addr = hook.current_render_context_addr();
+        assert_eq!(hook.export_address(), Some(render_context_addr));
+
+        let instructions = hook_instructions(&mut hook);
+        assert!(instructions.iter().any(|instruction| {
+            instruction.op0_register() == Register::RAX
+                && instruction.memory_base() == Register::R15
+                && instruction.memory_displacement64() == 0
+        }));
+        assert!(stores_to(&instructions, render_context_addr, Register::RAX));
+
+        assert!(matches!(hook.render_context(), Err(WizWalkerError::NullPointer { .. })));
+        memory.write_typed(render_context_addr, 0x2_0000_6000_usize).unwrap();
+        assert_eq!(hook.render_context().unwrap().base_address(), 0x2_0000_6000);
+
+        assert_unhooks(&mut memory, &mut hook, &RENDER_CONTEXT_SITE_CODE);
+        assert!(matches!(hook.render_context(), Err(WizWalkerError::HookNotActive { .. })));
+    }
+}
@@ -0,0 +1,267 @@
+use bytemuck::Pod;
+
+use crate::error::{Result, WizWalkerError};
+use super::memory_reader::WizWalkerMemoryReader;
+use super::type_dump::{dumped_offset, resolve_offset};
+
+
+/// A game object at a fixed address, read through whichever reader is passed in.
+///
+/// Objects hold only their base address and read lazily: every accessor reads
+/// the current value, so objects stay cheap to copy around between ticks.
+/// Implementors only provide the address; every field accessor is then a
+/// one line offset declaration over these helpers. Use `memory_object!` to
+/// declare the struct itself.
+pub trait MemoryObject: Sized {
+    /// The object's class in the type dump, if it is a `PropertyClass`.
+    const TYPE_NAME: Option<&'static str> = None;
+
+    fn from_address(base_address: usize) -> Self;
+
+    fn base_address(&self) -> usize;
+
+    /// Offset of `property` in the installed type dump, or `fallback` when
+    /// there is no dump or it lacks the property.
+    fn field_offset(property: &str, fallback: usize) -> usize {
+        match Self::TYPE_NAME {
+            Some(type_name) => resolve_offset(type_name, property, fallback),
+            None => fallback
+        }
+    }
+
+    /// Offset of `property` in the installed type dump, for fields without a
+    /// built-in offset. Fails when there is no dump or it lacks the property.
+    fn dumped_field_offset(property: &str) -> Result<usize> {
+        match Self::TYPE_NAME {
+            Some(type_name) => dumped_offset(type_name, property),
+            None => Err(WizWalkerError::TypeNotFound { name: std::any::type_name::<Self>().to_string() })
+        }
+    }
+
+    /// Reinterprets the object at the same address as another object type.
+    fn cast<O: MemoryObject>(&self) -> O {
+        O::from_address(self.base_address())
+    }
+
+    fn read_value_from_offset<T: Pod, R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize) -> Result<T> {
+        reader.read_typed(self.base_address() + offset)
+    }
+
+    fn write_value_to_offset<T: Pod + Send, R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize, value: T) -> Result<()> {
+        reader.write_typed(self.base_address() + offset, value)
+    }
+
+    /// Reads a one byte `bool`; any non-zero byte is `true`.
+    fn read_bool_from_offset<R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize) -> Result<bool> {
+        Ok(self.read_value_from_offset::<u8, R>(reader, offset)? != 0)
+    }
+
+    fn write_bool_to_offset<R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize, value: bool) -> Result<()> {
+        self.write_value_to_offset(reader, offset, value as u8)
+    }
+
+    /// Reads a 4 byte enum, failing on values `E` does not know.
+    fn read_enum_from_offset<E, R>(&self, reader: &mut R, offset: usize) -> Result<E>
+    where
+        E: TryFrom<i32, Error = WizWalkerError>,
+        R: WizWalkerMemoryReader,
+    {
+        E::try_from(self.read_value_from_offset::<i32, R>(reader, offset)?)
+    }
+
+    fn read_string_from_offset<R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize) -> Result<String> {
+        reader.read_std_string(self.base_address() + offset)
+    }
+
+    /// Overwrites a `std::string` in place; fails if `value` exceeds its capacity.
+    fn write_string_to_offset<R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize, value: &str) -> Result<()> {
+        reader.write_std_string(self.base_address() + offset, value)
+    }
+
+    fn read_wide_string_from_offset<R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize) -> Result<String> {
+        reader.read_std_wstring(self.base_address() + offset)
+    }
+
+    /// Overwrites a `std::wstring` in place; fails if `value` exceeds its capacity.
+    fn write_wide_string_to_offset<R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize, value: &str) -> Result<()> {
+        reader.write_std_wstring(self.base_address() + offset, value)
+    }
+
+    fn read_vector_from_offset<T: Pod, R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize) -> Result<Vec<T>> {
+        reader.read_std_vector(self.base_address() + offset)
+    }
+
+    /// Addresses held by a `std::vector<SharedPointer<T>>`, nulls skipped.
+    fn read_shared_vector_from_offset<R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize) -> Result<Vec<usize>> {
+        reader.read_shared_vector(self.base_address() + offset)
+    }
+
+    /// Follows a pointer to another object, or `None` when it is null.
+    fn read_object_from_offset<O: MemoryObject, R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize) -> Result<Option<O>> {
+        match self.read_value_from_offset::<usize, R>(reader, offset)? {
+            0 => Ok(None),
+            address => Ok(Some(O::from_address(address)))
+        }
+    }
+
+    /// Objects held by a `std::vector<SharedPointer<T>>`.
+    fn read_shared_vector_objects<O: MemoryObject, R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize) -> Result<Vec<O>> {
+        Ok(self.read_shared_vector_from_offset(reader, offset)?
+            .into_iter()
+            .map(O::from_address)
+            .collect())
+    }
+}
+
+
+/// Declares a `Copy` memory object holding only its base address.
+///
+/// Naming the object's class lets its fields be looked up in the type dump.
+///
+/// ```ignore
+/// memory_object! {
+///     /// A spell in the player's deck.
+///     pub struct Spell: "Spell";
+/// }
+///
+/// impl Spell {
+///     pub fn template_id<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<u32> {
+///         self.read_value_from_offset(reader, Self::field_offset("m_templateID", 128))
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! memory_object {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident;
+    ) => {
+        $crate::memory_object!(@declare $(#[$meta])* $vis struct $name, None);
+    };
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: $type_name:literal;
+    ) => {
+        $crate::memory_object!(@declare $(#[$meta])* $vis struct $name, Some($type_name));
+    };
+    (
+        @declare $(#[$meta:meta])* $vis:vis struct $name:ident, $type_name:expr
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        $vis struct $name {
+            base_address: usize,
+        }
+
+        impl $name {
+            pub fn new(base_address: usize) -> Self {
+                Self { base_address }
+            }
+        }
+
+        impl $crate::memory::memory_object::MemoryObject for $name {
+            const TYPE_NAME: Option<&'static str> = $type_name;
+
+            fn from_address(base_address: usize) -> Self {
+                Self { base_address }
+            }
+
+            fn base_address(&self) -> usize {
+                self.base_address
+            }
+        }
+    };
+}
+
+
+memory_object! {
+    /// Any object, for reading layouts that have no dedicated type yet.
+    pub struct DynamicMemoryObject;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::fake_memory::{FakeMemory, FAKE_MODULE_BASE};
+    use crate::memory::memory_objects::enums::DuelPhase;
+
+    const OBJECT: usize = FAKE_MODULE_BASE;
+    const CHILD: usize = FAKE_MODULE_BASE + 0x1000;
+    const HEAP: usize = FAKE_MODULE_BASE + 0x2000;
+
+    fn object_memory() -> FakeMemory {
+        FakeMemory::with_zeroed_regions(&[(OBJECT, 0x100), (CHILD, 0x100), (HEAP, 0x100)])
+    }
+
+    #[test]
+    fn values_round_trip_through_offsets() {
+        let mut memory = object_memory();
+        let object = DynamicMemoryObject::new(OBJECT);
+
+        object.write_value_to_offset(&mut memory, 0x10, 1.5f32).unwrap();
+        object.write_bool_to_offset(&mut memory, 0x14, true).unwrap();
+        object.write_value_to_offset(&mut memory, 0x18, 4i32).unwrap();
+
+        assert_eq!(object.read_value_from_offset::<f32, _>(&mut memory, 0x10).unwrap(), 1.5);
+        assert!(object.read_bool_from_offset(&mut memory, 0x14).unwrap());
+        assert_eq!(object.read_enum_from_offset::<DuelPhase, _>(&mut memory, 0x18).unwrap(), DuelPhase::Execution);
+
+        object.write_value_to_offset(&mut memory, 0x18, 8i32).unwrap();
+        assert!(matches!(
+            object.read_enum_from_offset::<DuelPhase, _>(&mut memory, 0x18),
+            Err(WizWalkerError::InvalidEnumValue { name: "DuelPhase", value: 8 })
+        ));
+    }
+
+    #[test]
+    fn strings_are_read_inline_and_written_in_place() {
+        let mut memory = object_memory();
+        let object = DynamicMemoryObject::new(OBJECT);
+
+        // An inline `std::string`: 16 byte buffer, then size and capacity.
+        memory.write_bytes(OBJECT + 0x20, b"Gamma".to_vec()).unwrap();
+        object.write_value_to_offset(&mut memory, 0x30, 5u64).unwrap();
+        object.write_value_to_offset(&mut memory, 0x38, 15u64).unwrap();
+
+        assert_eq!(object.read_string_from_offset(&mut memory, 0x20).unwrap(), "Gamma");
+
+        object.write_string_to_offset(&mut memory, 0x20, "Malistaire").unwrap();
+        assert_eq!(object.read_string_from_offset(&mut memory, 0x20).unwrap(), "Malistaire");
+        assert!(object.write_string_to_offset(&mut memory, 0x20, "Merle Ambrose's Office").is_err());
+    }
+
+    #[test]
+    fn pointers_and_shared_vectors_become_objects() {
+        let mut memory = object_memory();
+        let object = DynamicMemoryObject::new(OBJECT);
+
+        object.write_value_to_offset(&mut memory, 0x40, CHILD).unwrap();
+        assert_eq!(
+            object.read_object_from_offset::<DynamicMemoryObject, _>(&mut memory, 0x40).unwrap(),
+            Some(DynamicMemoryObject::new(CHILD))
+        );
+        assert_eq!(object.read_object_from_offset::<DynamicMemoryObject, _>(&mut memory, 0x48).unwrap(), None);
+
+        // Three shared pointers, the middle one null.
+        let shared_pointers: [u64; 6] = [CHILD as u64, 0, 0, 0, OBJECT as u64, 0];
+        memory.write_bytes(HEAP, bytemuck::cast_slice(&shared_pointers).to_vec()).unwrap();
+        object.write_value_to_offset(&mut memory, 0x50, HEAP as u64).unwrap();
+        object.write_value_to_offset(&mut memory, 0x58, (HEAP + 48) as u64).unwrap();
+        object.write_value_to_offset(&mut memory, 0x60, (HEAP + 48) as u64).unwrap();
+
+        let children: Vec<DynamicMemoryObject> = object.read_shared_vector_objects(&mut memory, 0x50).unwrap();
+        assert_eq!(children, vec![DynamicMemoryObject::new(CHILD), DynamicMemoryObject::new(OBJECT)]);
+    }
+
+    #[test]
+    fn unmapped_objects_fail_to_read() {
+        let mut memory = object_memory();
+        let object = DynamicMemoryObject::new(0x1000);
+
+        assert!(matches!(
+            object.read_value_from_offset::<u32, _>(&mut memory, 0x8),
+            Err(WizWalkerError::ReadFailed { address: 0x1008, size: 4 })
+        ));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::Result;
+use crate::memory::memory_object::MemoryObject;
+use crate::memory::memory_reader::WizWalkerMemoryReader;
+use crate::memory_object;
+use crate::utils::{Orient, XYZ};
+
+
+memory_object! {
+    /// The physical body of a game object.
+    ///
+    /// Writes only move what the client draws; the game's own movement update
+    /// can overwrite them, so use the teleport hook for moves that must stick.
+    pub struct ActorBody: "ActorBody";
+}
+
+impl ActorBody {
+    pub fn position<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<XYZ> {
+        self.read_value_from_offset(reader, Self::field_offset("m_position", 88))
+    }
+
+    pub fn write_position<R: WizWalkerMemoryReader>(&self, reader: &mut R, position: XYZ) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_position", 88), position)
+    }
+
+    pub fn pitch<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_fPitch", 100))
+    }
+
+    pub fn write_pitch<R: WizWalkerMemoryReader>(&self, reader: &mut R, pitch: f32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_fPitch", 100), pitch)
+    }
+
+    pub fn roll<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_fRoll", 104))
+    }
+
+    pub fn write_roll<R: WizWalkerMemoryReader>(&self, reader: &mut R, roll: f32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_fRoll", 104), roll)
+    }
+
+    pub fn yaw<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_fYaw", 108))
+    }
+
+    pub fn write_yaw<R: WizWalkerMemoryReader>(&self, reader: &mut R, yaw: f32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_fYaw", 108), yaw)
+    }
+
+    /// Pitch, roll and yaw, which the game stores next to each other.
+    pub fn orientation<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Orient> {
+        self.read_value_from_offset(reader, Self::field_offset("m_fPitch", 100))
+    }
+
+    pub fn write_orientation<R: WizWalkerMemoryReader>(&self, reader: &mut R, orientation: Orient) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_fPitch", 100), orientation)
+    }
+
+    pub fn scale<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_fScale", 112))
+    }
+
+    /// Takes effect once the model is next updated; see `write_model_update_scheduled`.
+    pub fn write_scale<R: WizWalkerMemoryReader>(&self, reader: &mut R, scale: f32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_fScale", 112), scale)
+    }
+
+    pub fn height<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_fHeight", 132))
+    }
+
+    pub fn write_height<R: WizWalkerMemoryReader>(&self, reader: &mut R, height: f32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_fHeight", 132), height)
+    }
+
+    /// Whether the client rebuilds the model on its next frame.
+    pub fn model_update_scheduled<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<bool> {
+        self.read_bool_from_offset(reader, Self::field_offset("m_bModelUpdateScheduled", 136))
+    }
+
+    pub fn write_model_update_scheduled<R: WizWalkerMemoryReader>(&self, reader: &mut R, scheduled: bool) -> Result<()> {
+        self.write_bool_to_offset(reader, Self::field_offset("m_bModelUpdateScheduled", 136), scheduled)
+    }
+
+    /// Unit vector the body faces.
+    pub fn direction<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<XYZ> {
+        Ok(self.orientation(reader)?.direction())
+    }
+
+    /// Turns the body to face `target` on the ground plane.
+    pub fn turn_towards<R: WizWalkerMemoryReader>(&self, reader: &mut R, target: XYZ) -> Result<()> {
+        let position = self.position(reader)?;
+        self.write_yaw(reader, Orient::looking_at(&position, &target).yaw)
+    }
+
+    /// Position `distance` units ahead of the body.
+    pub fn position_ahead<R: WizWalkerMemoryReader>(&self, reader: &mut R, distance: f32) -> Result<XYZ> {
+        let direction = self.direction(reader)?;
+        let position = self.position(reader)?;
+
+        Ok(position + XYZ::new(direction.x * distance, direction.y * distance, direction.z * distance))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::fake_memory::{FakeMemory, FAKE_MODULE_BASE};
+
+    const BODY: usize = FAKE_MODULE_BASE;
+
+    fn assert_close(actual: XYZ, expected: XYZ) {
+        assert!(actual.distance(&expected) < 1e-4, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn orientation_shares_the_pitch_roll_and_yaw_fields() {
+        let mut memory = FakeMemory::with_zeroed_regions(&[(BODY, 0x100)]);
+        let body = ActorBody::new(BODY);
+
+        body.write_orientation(&mut memory, Orient::new(0.1, 0.2, 0.3)).unwrap();
+        assert_eq!(body.pitch(&mut memory).unwrap(), 0.1);
+        assert_eq!(body.roll(&mut memory).unwrap(), 0.2);
+        assert_eq!(body.yaw(&mut memory).unwrap(), 0.3);
+    }
+
+    #[test]
+    fn turning_faces_the_target() {
+        let mut memory = FakeMemory::with_zeroed_regions(&[(BODY, 0x100)]);
+        let body = ActorBody::new(BODY);
+
+        body.write_position(&mut memory, XYZ::new(100.0, 100.0, 0.0)).unwrap();
+
+        for target in [XYZ::new(100.0, 0.0, 0.0), XYZ::new(0.0, 100.0, 0.0), XYZ::new(200.0, 200.0, 0.0)] {
+            body.turn_towards(&mut memory, target).unwrap();
+
+            let mut expected = target - XYZ::new(100.0, 100.0, 0.0);
+            let length = expected.length();
+            expected = XYZ::new(expected.x / length, expected.y / length, 0.0);
+
+            assert_close(body.direction(&mut memory).unwrap(), expected);
+        }
+
+        body.write_yaw(&mut memory, 0.0).unwrap();
+        assert_close(body.position_ahead(&mut memory, 50.0).unwrap(), XYZ::new(100.0, 50.0, 0.0));
+    }
+}
@@ -0,0 +1,184 @@
+use crate::error::{Result, WizWalkerError};
+use crate::file_readers::cache_handler::CacheHandler;
+use crate::memory::memory_object::MemoryObject;
+use crate::memory::memory_reader::WizWalkerMemoryReader;
+use crate::memory_object;
+use crate::utils::{Orient, XYZ};
+
+use super::constants::WIZARD_SPEED;
+use super::enums::ObjectType;
+use super::game_stats::GameStats;
+
+
+memory_object! {
+    /// The template a game object was created from.
+    pub struct ObjectTemplate: "WizGameObjectTemplate";
+}
+
+impl ObjectTemplate {
+    pub fn object_name<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<String> {
+        self.read_string_from_offset(reader, Self::field_offset("m_objectName", 72))
+    }
+
+    pub fn template_id<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<u32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_templateID", 96))
+    }
+
+    /// Langcode of the name shown in game, e.g. `"ObjectData_Gamma"`.
+    pub fn display_name<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<String> {
+        self.read_string_from_offset(reader, Self::field_offset("m_displayName", 168))
+    }
+
+    pub fn object_type<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<ObjectType> {
+        self.read_enum_from_offset(reader, Self::field_offset("m_nObjectType", 200))
+    }
+}
+
+
+/// Offset of the stats pointer inside a client object; it is not a property, so no dump names it.
+pub(crate) const GAME_STATS_OFFSET: usize = 544;
+
+
+memory_object! {
+    /// A game object in the client's world.
+    pub struct ClientObject: "ClientObject";
+}
+
+impl ClientObject {
+    pub fn global_id_full<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<u64> {
+        self.read_value_from_offset(reader, Self::field_offset("m_globalID", 72))
+    }
+
+    pub fn perm_id<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<u64> {
+        self.read_value_from_offset(reader, Self::field_offset("m_permID", 80))
+    }
+
+    /// The template the object was created from, or `None` when it has none.
+    pub fn object_template<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Option<ObjectTemplate>> {
+        self.read_object_from_offset(reader, Self::field_offset("m_pObjectTemplate", 88))
+    }
+
+    pub fn template_id_full<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<u64> {
+        self.read_value_from_offset(reader, Self::field_offset("m_templateID", 96))
+    }
+
+    /// Name of the object's template, looked up in the cached template ids.
+    pub fn template_name<R: WizWalkerMemoryReader>(&self, reader: &mut R, cache_handler: &mut CacheHandler) -> Result<Option<String>> {
+        let template_id = i32::try_from(self.template_id_full(reader)?)?;
+        cache_handler.get_template_name(template_id)
+    }
+
+    pub fn debug_name<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<String> {
+        self.read_string_from_offset(reader, Self::field_offset("m_debugName", 104))
+    }
+
+    pub fn display_key<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<String> {
+        self.read_string_from_offset(reader, Self::field_offset("m_displayKey", 136))
+    }
+
+    /// Langcode of the object's in-game name, from its template.
+    pub fn display_name<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Option<String>> {
+        match self.object_template(reader)? {
+            Some(template) => Ok(Some(template.display_name(reader)?)),
+            None => Ok(None)
+        }
+    }
+
+    pub fn object_type<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<ObjectType> {
+        match self.object_template(reader)? {
+            Some(template) => template.object_type(reader),
+            None => Ok(ObjectType::Undefined)
+        }
+    }
+
+    pub fn location<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<XYZ> {
+        self.read_value_from_offset(reader, Self::field_offset("m_location", 168))
+    }
+
+    pub fn write_location<R: WizWalkerMemoryReader>(&self, reader: &mut R, location: XYZ) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_location", 168), location)
+    }
+
+    pub fn orientation<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Orient> {
+        self.read_value_from_offset(reader, Self::field_offset("m_orientation", 180))
+    }
+
+    pub fn write_orientation<R: WizWalkerMemoryReader>(&self, reader: &mut R, orientation: Orient) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_orientation", 180), orientation)
+    }
+
+    /// Percent added to the base movement speed, e.g. 40 from a 40% mount.
+    pub fn speed_multiplier<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i16> {
+        self.read_value_from_offset(reader, Self::field_offset("m_speedMultiplier", 192))
+    }
+
+    pub fn write_speed_multiplier<R: WizWalkerMemoryReader>(&self, reader: &mut R, multiplier: i16) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_speedMultiplier", 192), multiplier)
+    }
+
+    /// Movement speed in units per second, from `WIZARD_SPEED` and the speed multiplier.
+    pub fn movement_speed<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        let multiplier = self.speed_multiplier(reader)? as f32;
+        Ok(WIZARD_SPEED as f32 * (100.0 + multiplier) / 100.0)
+    }
+
+    pub fn mobile_id<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<u16> {
+        self.read_value_from_offset(reader, Self::field_offset("m_nMobileID", 194))
+    }
+
+    pub fn scale<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_fScale", 196))
+    }
+
+    pub fn write_scale<R: WizWalkerMemoryReader>(&self, reader: &mut R, scale: f32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_fScale", 196), scale)
+    }
+
+    /// The object this one is attached to, or `None` for top-level objects.
+    pub fn parent<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Option<ClientObject>> {
+        self.read_object_from_offset(reader, Self::field_offset("m_pParent", 208))
+    }
+
+    /// The object's stats, or `None` for objects without any, e.g. most NPCs.
+    pub fn game_stats<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Option<GameStats>> {
+        self.read_object_from_offset(reader, GAME_STATS_OFFSET)
+    }
+
+    /// Addresses of the object's inactive `BehaviorInstance`s.
+    pub fn inactive_behaviors<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Vec<usize>> {
+        self.read_shared_vector_from_offset(reader, Self::field_offset("m_inactiveBehaviors", 224))
+    }
+
+    pub fn children<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Vec<ClientObject>> {
+        self.read_shared_vector_objects(reader, Self::field_offset("m_children", 384))
+    }
+}
+
+
+/// The local player's client object, followed through the client hook's export.
+///
+/// The game replaces the player's object on zone changes, so the address is
+/// read again on every call instead of being kept. Only valid while the
+/// client hook that owns the export is active.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentClientObject {
+    export_address: usize,
+}
+
+impl CurrentClientObject {
+    pub fn new(export_address: usize) -> Self {
+        Self { export_address }
+    }
+
+    pub fn export_address(&self) -> usize {
+        self.export_address
+    }
+
+    /// The player's client object as of now.
+    pub fn client_object<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<ClientObject> {
+        match reader.read_typed::<usize>(self.export_address)? {
+            0 => Err(WizWalkerError::NullPointer { address: self.export_address }),
+            address => Ok(ClientObject::new(address))
+        }
+    }
+}
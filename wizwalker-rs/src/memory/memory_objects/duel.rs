@@ -0,0 +1,88 @@
+use crate::error::Result;
+use crate::memory::memory_object::MemoryObject;
+use crate::memory::memory_reader::WizWalkerMemoryReader;
+use crate::memory_object;
+
+use super::enums::{DuelExecutionOrder, DuelPhase, SigilInitiativeSwitchMode};
+
+
+memory_object! {
+    /// A duel in the client.
+    pub struct Duel: "Duel";
+}
+
+impl Duel {
+    pub fn duel_id_full<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<u64> {
+        self.read_value_from_offset(reader, Self::field_offset("m_duelID", 72))
+    }
+
+    /// Addresses of the duel's `CombatParticipant`s.
+    pub fn participant_list<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Vec<usize>> {
+        self.read_shared_vector_from_offset(reader, Self::field_offset("m_participantList", 80))
+    }
+
+    pub fn dynamic_turn<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_dynamicTurn", 120))
+    }
+
+    pub fn dynamic_turn_subcircles<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_dynamicTurnSubcircles", 124))
+    }
+
+    pub fn dynamic_turn_counter<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_dynamicTurnCounter", 128))
+    }
+
+    pub fn planning_timer<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_planningTimer", 144))
+    }
+
+    pub fn yaw<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_yaw", 160))
+    }
+
+    pub fn disable_timer<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<bool> {
+        self.read_bool_from_offset(reader, Self::field_offset("m_disableTimer", 176))
+    }
+
+    pub fn tutorial_mode<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<bool> {
+        self.read_bool_from_offset(reader, Self::field_offset("m_tutorialMode", 177))
+    }
+
+    pub fn first_team_to_act<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_firstTeamToAct", 180))
+    }
+
+    pub fn pvp<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<bool> {
+        self.read_bool_from_offset(reader, Self::field_offset("m_pvp", 184))
+    }
+
+    pub fn raid<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<bool> {
+        self.read_bool_from_offset(reader, Self::field_offset("m_raid", 185))
+    }
+
+    pub fn duel_phase<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<DuelPhase> {
+        self.read_enum_from_offset(reader, Self::field_offset("m_duelPhase", 196))
+    }
+
+    pub fn round_num<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_roundNum", 204))
+    }
+
+    pub fn initiative_switch_mode<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<SigilInitiativeSwitchMode> {
+        self.read_enum_from_offset(reader, Self::field_offset("m_initiativeSwitchMode", 384))
+    }
+
+    pub fn initiative_switch_rounds<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_initiativeSwitchRounds", 388))
+    }
+
+    pub fn execution_order<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<DuelExecutionOrder> {
+        self.read_enum_from_offset(reader, Self::field_offset("m_executionOrder", 456))
+    }
+
+    /// Whether the duel has not ended yet.
+    pub fn is_active<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<bool> {
+        Ok(!matches!(self.duel_phase(reader)?, DuelPhase::Ended | DuelPhase::Max))
+    }
+}
@@ -1,5 +1,7 @@
 use bitflags::bitflags;
 
+use crate::error::WizWalkerError;
+
 
 #[derive(Debug)]
 pub enum HangingDisposition {
@@ -9,7 +11,7 @@ pub enum HangingDisposition {
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DuelPhase {
     Starting = 0,
     PrePlanning = 1,
@@ -22,20 +24,64 @@ pub enum DuelPhase {
     Max = 10
 }
 
+impl TryFrom<i32> for DuelPhase {
+    type Error = WizWalkerError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DuelPhase::Starting),
+            1 => Ok(DuelPhase::PrePlanning),
+            2 => Ok(DuelPhase::Planning),
+            3 => Ok(DuelPhase::PreExecution),
+            4 => Ok(DuelPhase::Execution),
+            5 => Ok(DuelPhase::Resolution),
+            6 => Ok(DuelPhase::Victory),
+            7 => Ok(DuelPhase::Ended),
+            10 => Ok(DuelPhase::Max),
+            _ => Err(WizWalkerError::InvalidEnumValue { name: "DuelPhase", value: value as i64 })
+        }
+    }
+}
 
-#[derive(Debug)]
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SigilInitiativeSwitchMode {
     None,
     Reroll,
     Switch
 }
 
-#[derive(Debug)]
+impl TryFrom<i32> for SigilInitiativeSwitchMode {
+    type Error = WizWalkerError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SigilInitiativeSwitchMode::None),
+            1 => Ok(SigilInitiativeSwitchMode::Reroll),
+            2 => Ok(SigilInitiativeSwitchMode::Switch),
+            _ => Err(WizWalkerError::InvalidEnumValue { name: "SigilInitiativeSwitchMode", value: value as i64 })
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DuelExecutionOrder {
     Sequential,
     Alternating
 }
 
+impl TryFrom<i32> for DuelExecutionOrder {
+    type Error = WizWalkerError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DuelExecutionOrder::Sequential),
+            1 => Ok(DuelExecutionOrder::Alternating),
+            _ => Err(WizWalkerError::InvalidEnumValue { name: "DuelExecutionOrder", value: value as i64 })
+        }
+    }
+}
+
 
 #[derive(Debug)]
 pub enum PipAcquiredByEnum {
@@ -286,7 +332,7 @@ pub enum EffectTarget {
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ObjectType {
     Undefined = 0,
     Player = 1,
@@ -320,8 +366,48 @@ pub enum ObjectType {
     Plant = 29,
 }
 
+impl TryFrom<i32> for ObjectType {
+    type Error = WizWalkerError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ObjectType::Undefined),
+            1 => Ok(ObjectType::Player),
+            2 => Ok(ObjectType::Npc),
+            3 => Ok(ObjectType::Prop),
+            4 => Ok(ObjectType::Object),
+            5 => Ok(ObjectType::House),
+            6 => Ok(ObjectType::Key),
+            7 => Ok(ObjectType::OldKey),
+            8 => Ok(ObjectType::Deed),
+            9 => Ok(ObjectType::Mail),
+            10 => Ok(ObjectType::EquipHead),
+            11 => Ok(ObjectType::EquipChest),
+            12 => Ok(ObjectType::EquipLegs),
+            13 => Ok(ObjectType::EquipHands),
+            14 => Ok(ObjectType::EquipFinger),
+            15 => Ok(ObjectType::EquipFeet),
+            16 => Ok(ObjectType::EquipEar),
+            17 => Ok(ObjectType::Recipe),
+            18 => Ok(ObjectType::BuildingBlock),
+            19 => Ok(ObjectType::BuildingBlockSolid),
+            20 => Ok(ObjectType::Golf),
+            21 => Ok(ObjectType::Door),
+            22 => Ok(ObjectType::Pet),
+            23 => Ok(ObjectType::Fabric),
+            24 => Ok(ObjectType::Window),
+            25 => Ok(ObjectType::Roof),
+            26 => Ok(ObjectType::Horse),
+            27 => Ok(ObjectType::Structure),
+            28 => Ok(ObjectType::HousingTexture),
+            29 => Ok(ObjectType::Plant),
+            _ => Err(WizWalkerError::InvalidEnumValue { name: "ObjectType", value: value as i64 })
+        }
+    }
+}
+
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MagicSchool {
     Ice = 72777,
     Sun = 78483,
@@ -341,6 +427,30 @@ pub enum MagicSchool {
     Cantrips = 1760873841
 }
 
+impl MagicSchool {
+    /// Index of the school in the game's per-school stat vectors.
+    pub fn stat_index(self) -> usize {
+        match self {
+            MagicSchool::Fire => 0,
+            MagicSchool::Ice => 1,
+            MagicSchool::Storm => 2,
+            MagicSchool::Myth => 3,
+            MagicSchool::Life => 4,
+            MagicSchool::Death => 5,
+            MagicSchool::Balance => 6,
+            MagicSchool::Star => 7,
+            MagicSchool::Sun => 8,
+            MagicSchool::Moon => 9,
+            MagicSchool::Gardening => 10,
+            MagicSchool::Shadow => 11,
+            MagicSchool::Fishing => 12,
+            MagicSchool::Cantrips => 13,
+            MagicSchool::CastleMagic => 14,
+            MagicSchool::WhirlyBurly => 15,
+        }
+    }
+}
+
 
 #[derive(Debug)]
 enum FogMode {
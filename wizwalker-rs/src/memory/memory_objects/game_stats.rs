@@ -0,0 +1,254 @@
+use crate::error::Result;
+use crate::memory::memory_object::MemoryObject;
+use crate::memory::memory_reader::WizWalkerMemoryReader;
+use crate::memory_object;
+
+use super::enums::MagicSchool;
+
+
+memory_object! {
+    /// The local wizard's stats.
+    ///
+    /// Per-school stats are read from the game's per-school vectors, without
+    /// the universal `*_all` bonus added on top.
+    ///
+    /// Current energy, experience, pip conversion and shadow pip rating are
+    /// newer than the built-in layout, so they have no fallback offsets and
+    /// fail with `PropertyNotFound` unless a type dump is installed.
+    pub struct GameStats: "WizGameStats";
+}
+
+impl GameStats {
+    pub fn base_hitpoints<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_baseHitpoints", 80))
+    }
+
+    /// Health from gear, pets and other bonuses.
+    pub fn bonus_hitpoints<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_bonusHitpoints", 216))
+    }
+
+    pub fn max_hitpoints<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        Ok(self.base_hitpoints(reader)? + self.bonus_hitpoints(reader)?)
+    }
+
+    pub fn current_hitpoints<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_currentHitpoints", 108))
+    }
+
+    /// Only changes what this client shows until the server next sends the player's health.
+    pub fn write_current_hitpoints<R: WizWalkerMemoryReader>(&self, reader: &mut R, hitpoints: i32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_currentHitpoints", 108), hitpoints)
+    }
+
+    pub fn base_mana<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_baseMana", 84))
+    }
+
+    pub fn bonus_mana<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_bonusMana", 220))
+    }
+
+    pub fn max_mana<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        Ok(self.base_mana(reader)? + self.bonus_mana(reader)?)
+    }
+
+    pub fn current_mana<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_currentMana", 128))
+    }
+
+    /// Only changes what this client shows until the server next sends the player's mana.
+    pub fn write_current_mana<R: WizWalkerMemoryReader>(&self, reader: &mut R, mana: i32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_currentMana", 128), mana)
+    }
+
+    pub fn base_energy<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_energyMax", 104))
+    }
+
+    pub fn bonus_energy<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_bonusEnergy", 236))
+    }
+
+    pub fn max_energy<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        Ok(self.base_energy(reader)? + self.bonus_energy(reader)?)
+    }
+
+    pub fn current_energy<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::dumped_field_offset("m_currentEnergy")?)
+    }
+
+    pub fn level<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_referenceLevel", 316))
+    }
+
+    pub fn highest_level_on_account<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_highestCharacterLevelOnAccount", 320))
+    }
+
+    pub fn experience<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::dumped_field_offset("m_experience")?)
+    }
+
+    pub fn current_gold<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_currentGold", 112))
+    }
+
+    /// Most gold the wizard can carry.
+    pub fn max_gold<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<i32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_baseGoldPouch", 88))
+    }
+
+    pub fn potion_max<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_potionMax", 160))
+    }
+
+    /// Potion charges left, fractional while a charge refills.
+    pub fn potion_charge<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_potionCharge", 164))
+    }
+
+    pub fn write_potion_charge<R: WizWalkerMemoryReader>(&self, reader: &mut R, charge: f32) -> Result<()> {
+        self.write_value_to_offset(reader, Self::field_offset("m_potionCharge", 164), charge)
+    }
+
+    /// Outgoing damage bonus for `school`, where 0.25 is +25%.
+    pub fn damage<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::field_offset("m_dmgBonusPercent", 336), school)
+    }
+
+    pub fn damage_all<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_dmgBonusPercentAll", 696))
+    }
+
+    pub fn flat_damage<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::field_offset("m_dmgBonusFlat", 360), school)
+    }
+
+    pub fn accuracy<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::field_offset("m_accBonusPercent", 384), school)
+    }
+
+    pub fn accuracy_all<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_accBonusPercentAll", 704))
+    }
+
+    /// Armor piercing for `school`.
+    pub fn pierce<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::field_offset("m_apBonusPercent", 408), school)
+    }
+
+    pub fn pierce_all<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_apBonusPercentAll", 708))
+    }
+
+    /// Incoming damage reduction for `school`.
+    pub fn resist<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::field_offset("m_dmgReducePercent", 432), school)
+    }
+
+    pub fn resist_all<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_dmgReducePercentAll", 712))
+    }
+
+    pub fn flat_resist<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::field_offset("m_dmgReduceFlat", 456), school)
+    }
+
+    pub fn critical_rating<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::field_offset("m_criticalHitRatingBySchool", 648), school)
+    }
+
+    pub fn critical_rating_all<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_criticalHitRatingAll", 248))
+    }
+
+    pub fn block_rating<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::field_offset("m_blockRatingBySchool", 672), school)
+    }
+
+    pub fn block_rating_all<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_blockRatingAll", 252))
+    }
+
+    /// Chance of a pip becoming a power pip of `school`'s type.
+    pub fn pip_conversion_rating<R: WizWalkerMemoryReader>(&self, reader: &mut R, school: MagicSchool) -> Result<f32> {
+        self.read_school_stat(reader, Self::dumped_field_offset("m_pipConversionRatingPerSchool")?, school)
+    }
+
+    pub fn pip_conversion_rating_all<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::dumped_field_offset("m_pipConversionRatingAll")?)
+    }
+
+    pub fn shadow_pip_rating<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::dumped_field_offset("m_shadowPipRating")?)
+    }
+
+    /// Reads `school`'s entry of a per-school `std::vector<float>`.
+    ///
+    /// Schools past the end of the vector have no bonus and read as 0.
+    fn read_school_stat<R: WizWalkerMemoryReader>(&self, reader: &mut R, offset: usize, school: MagicSchool) -> Result<f32> {
+        let values: Vec<f32> = self.read_vector_from_offset(reader, offset)?;
+        Ok(values.get(school.stat_index()).copied().unwrap_or_default())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::fake_memory::{FakeMemory, FAKE_MODULE_BASE};
+    use crate::memory::memory_objects::client_object::ClientObject;
+
+    const STATS: usize = FAKE_MODULE_BASE;
+    const DAMAGE: usize = FAKE_MODULE_BASE + 0x1000;
+    const PLAYER: usize = FAKE_MODULE_BASE + 0x2000;
+
+    fn stats_memory() -> FakeMemory {
+        let mut memory = FakeMemory::with_zeroed_regions(&[(STATS, 0x400), (DAMAGE, 0x100), (PLAYER, 0x400)]);
+
+        memory.write_typed(STATS + 80, 2_000i32).unwrap();
+        memory.write_typed(STATS + 216, 1_500i32).unwrap();
+        memory.write_typed(STATS + 108, 3_100i32).unwrap();
+
+        // Damage for Fire, Ice and Storm only.
+        memory.write_typed(DAMAGE, [0.25f32, 0.5, 0.75]).unwrap();
+        memory.write_typed(STATS + 336, [DAMAGE, DAMAGE + 12, DAMAGE + 12]).unwrap();
+
+        memory
+    }
+
+    #[test]
+    fn max_stats_add_bonuses() {
+        let mut memory = stats_memory();
+        let stats = GameStats::new(STATS);
+
+        assert_eq!(stats.max_hitpoints(&mut memory).unwrap(), 3_500);
+        assert_eq!(stats.current_hitpoints(&mut memory).unwrap(), 3_100);
+
+        stats.write_current_hitpoints(&mut memory, 3_500).unwrap();
+        assert_eq!(stats.current_hitpoints(&mut memory).unwrap(), 3_500);
+    }
+
+    #[test]
+    fn school_stats_are_indexed_by_school() {
+        let mut memory = stats_memory();
+        let stats = GameStats::new(STATS);
+
+        assert_eq!(stats.damage(&mut memory, MagicSchool::Fire).unwrap(), 0.25);
+        assert_eq!(stats.damage(&mut memory, MagicSchool::Storm).unwrap(), 0.75);
+        assert_eq!(stats.damage(&mut memory, MagicSchool::Shadow).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn client_objects_point_at_their_stats() {
+        let mut memory = stats_memory();
+        let player = ClientObject::new(PLAYER);
+
+        assert!(player.game_stats(&mut memory).unwrap().is_none());
+
+        memory.write_typed(PLAYER + 544, STATS).unwrap();
+        let stats = player.game_stats(&mut memory).unwrap().unwrap();
+        assert_eq!(stats.max_hitpoints(&mut memory).unwrap(), 3_500);
+    }
+}
@@ -1,2 +1,8 @@
 pub mod constants;
-pub mod enums;
\ No newline at end of file
+pub mod enums;
+pub mod duel;
+pub mod window;
+pub mod render_context;
+pub mod client_object;
+pub mod game_stats;
+pub mod actor_body;
@@ -0,0 +1,17 @@
+use crate::error::Result;
+use crate::memory::memory_object::MemoryObject;
+use crate::memory::memory_reader::WizWalkerMemoryReader;
+use crate::memory_object;
+
+
+memory_object! {
+    /// The client's UI render context.
+    pub struct RenderContext: "RenderContext";
+}
+
+impl RenderContext {
+    /// Factor applied to window rectangles when drawn, from the in-game UI scale setting.
+    pub fn ui_scale<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<f32> {
+        self.read_value_from_offset(reader, Self::field_offset("m_uiScale", 152))
+    }
+}
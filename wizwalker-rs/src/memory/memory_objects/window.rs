@@ -0,0 +1,108 @@
+use crate::error::Result;
+use crate::memory::memory_object::MemoryObject;
+use crate::memory::memory_reader::WizWalkerMemoryReader;
+use crate::memory_object;
+use crate::utils::Rectangle;
+
+use super::enums::{WindowFlags, WindowStyle};
+
+
+/// Deepest UI tree walked by the recursive searches; the game's tree is far shallower.
+const MAX_WINDOW_DEPTH: usize = 64;
+
+
+memory_object! {
+    /// A UI window.
+    pub struct Window: "Window";
+}
+
+impl Window {
+    pub fn name<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<String> {
+        self.read_string_from_offset(reader, Self::field_offset("m_sName", 80))
+    }
+
+    pub fn children<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Vec<Window>> {
+        self.read_shared_vector_objects(reader, Self::field_offset("m_children", 112))
+    }
+
+    pub fn style<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<WindowStyle> {
+        Ok(WindowStyle::from_bits_retain(self.read_value_from_offset(reader, Self::field_offset("m_style", 152))?))
+    }
+
+    pub fn flags<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<WindowFlags> {
+        Ok(WindowFlags::from_bits_retain(self.read_value_from_offset(reader, Self::field_offset("m_flags", 156))?))
+    }
+
+    /// The window's rectangle, relative to its parent and unscaled.
+    pub fn window_rectangle<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Rectangle> {
+        self.read_value_from_offset(reader, Self::field_offset("m_window", 160))
+    }
+
+    /// The parent window, or `None` for the root.
+    pub fn parent<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Option<Window>> {
+        self.read_object_from_offset(reader, Self::field_offset("m_pParent", 0xD8))
+    }
+
+    /// Every ancestor, from the direct parent up to the root.
+    pub fn parents<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<Vec<Window>> {
+        let mut parents = Vec::new();
+        let mut current = self.parent(reader)?;
+
+        while let Some(parent) = current {
+            if parents.len() >= MAX_WINDOW_DEPTH {
+                break
+            }
+
+            parents.push(parent);
+            current = parent.parent(reader)?;
+        }
+
+        Ok(parents)
+    }
+
+    pub fn is_visible<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<bool> {
+        Ok(self.flags(reader)?.contains(WindowFlags::VISIBLE))
+    }
+
+    /// The window's rectangle in client coordinates, given the render context's UI scale.
+    pub fn scale_to_client<R: WizWalkerMemoryReader>(&self, reader: &mut R, ui_scale: f32) -> Result<Rectangle> {
+        let parent_rects = self.parents(reader)?
+            .into_iter()
+            .map(|parent| parent.window_rectangle(reader))
+            .collect::<Result<Vec<Rectangle>>>()?;
+
+        Ok(self.window_rectangle(reader)?.scale_to_client(&parent_rects, ui_scale))
+    }
+
+    pub fn get_child_by_name<R: WizWalkerMemoryReader>(&self, reader: &mut R, name: &str) -> Result<Option<Window>> {
+        for child in self.children(reader)? {
+            if child.name(reader)? == name {
+                return Ok(Some(child))
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Every window below this one named `name`.
+    pub fn get_windows_with_name<R: WizWalkerMemoryReader>(&self, reader: &mut R, name: &str) -> Result<Vec<Window>> {
+        let mut found = Vec::new();
+        let mut stack: Vec<(Window, usize)> = vec![(*self, 0)];
+
+        while let Some((window, depth)) = stack.pop() {
+            if depth >= MAX_WINDOW_DEPTH {
+                continue
+            }
+
+            for child in window.children(reader)?.into_iter().rev() {
+                if child.name(reader)? == name {
+                    found.push(child);
+                }
+
+                stack.push((child, depth + 1));
+            }
+        }
+
+        Ok(found)
+    }
+}
@@ -1,25 +1,39 @@
-use anyhow::{anyhow, Result};
 use pelite::pe::exports::By;
 use winapi::shared::minwindef::LPVOID;
 use winapi::um::handleapi::INVALID_HANDLE_VALUE;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::ptr::null_mut;
-use winapi::um::memoryapi::{ReadProcessMemory, VirtualAllocEx, VirtualFreeEx, VirtualQueryEx, WriteProcessMemory};
-use winapi::um::winnt::{HANDLE, MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_RELEASE, MEM_RESERVE, PAGE_READWRITE};
+use winapi::um::memoryapi::{ReadProcessMemory, VirtualAllocEx, VirtualFreeEx, VirtualProtectEx, VirtualQueryEx, WriteProcessMemory};
+use winapi::um::winnt::{
+    HANDLE, MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_FREE, MEM_RELEASE, MEM_RESERVE, PAGE_EXECUTE, PAGE_EXECUTE_READ,
+    PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, PAGE_READWRITE,
+};
 use winapi::um::{handleapi::CloseHandle, tlhelp32::MODULEENTRY32};
 use pelite::pe::{Pe, PeFile};
 use std::{fs::File, io::Read};
 use region::Protection;
 use winapi::ctypes::c_void;
 use regex::bytes::Regex;
-use winapi::um::processthreadsapi::CreateRemoteThread;
+use winapi::um::processthreadsapi::{CreateRemoteThread, FlushInstructionCache, GetProcessId};
+use winapi::um::sysinfoapi::{GetSystemInfo, SYSTEM_INFO};
 
-use bytemuck::{bytes_of, from_bytes, Pod, Zeroable};
+use bytemuck::{bytes_of, from_bytes, pod_read_unaligned, Pod, Zeroable};
 
+use crate::error::{Result, WizWalkerError};
 use crate::utils::{check_if_process_running, get_system_directory, module_from_name};
+use super::pointers::RemoteStruct;
+use super::read_cache::{page_of, PAGE_SIZE};
+use super::containers::{
+    std_map_node_pair_offset, std_pair_align, std_pair_value_offset, SharedPointer, StdListNodeLayout, StdMapNodeLayout,
+    StdNodeContainerLayout, StdStringLayout, StdVectorLayout, MAX_CONTAINER_SIZE, STD_LIST_NODE_VALUE_OFFSET,
+};
 
-fn addr_from_by(by: By<'_, PeFile<'_>>) -> Result<u32> {
+/// How far from its target `allocate_near` may place memory, leaving headroom inside the ±2GB `rel32` range.
+pub const NEAR_ALLOCATION_RANGE: usize = 0x7FF0_0000;
+
+
+fn addr_from_by(by: By<'_, PeFile<'_>>, symbol: &str) -> Result<u32> {
     let funcs = by.functions();
 
     let addr = funcs.get(0);
@@ -27,11 +41,27 @@ fn addr_from_by(by: By<'_, PeFile<'_>>) -> Result<u32> {
     match addr {
         Some(a) => Ok(a.to_owned()),
         None => {
-            Err(
-                anyhow!("Could not get address for function \"{:#?}\"", by.dll_name())
-            )
+            Err(WizWalkerError::SymbolNotFound {
+                module: format!("{:?}", by.dll_name()),
+                symbol: symbol.to_string(),
+            })
+        }
+    }
+}
+
+
+/// Puts back each page's saved protection, trying every page even if one fails.
+pub(crate) fn restore_protections<R: WizWalkerMemoryReader + ?Sized>(reader: &mut R, old_protections: &[(usize, u32)]) -> Result<()> {
+    let mut result = Ok(());
+
+    for (page, old_protection) in old_protections.iter().rev() {
+        let restored = reader.protect(*page, PAGE_SIZE, *old_protection);
+        if result.is_ok() {
+            result = restored.map(|_| ());
         }
     }
+
+    result
 }
 
 
@@ -89,11 +119,11 @@ pub trait WizWalkerMemoryReader {
         let exports = pe.exports()?;
 
         for export in exports.by() {
-            let (name, addr) = if let Ok(dll_name) = export.dll_name() {
-                (dll_name.to_str()?.to_string(), addr_from_by(export)?)
-            } else {
-                (format!("Ordinal {}", export.ordinal_base().to_string()), addr_from_by(export)?)
+            let name = match export.dll_name() {
+                Ok(dll_name) => dll_name.to_str()?.to_string(),
+                Err(_) => format!("Ordinal {}", export.ordinal_base())
             };
+            let addr = addr_from_by(export, &name)?;
 
             symbols.insert(name, addr);
         }
@@ -122,7 +152,7 @@ pub trait WizWalkerMemoryReader {
 
         if result == 0 {
             unsafe { CloseHandle(handle) };
-            return Err(anyhow!("Failed to query memory region"));
+            return Err(WizWalkerError::QueryFailed { address });
         }
 
         let next_region = mbi.BaseAddress as usize + mbi.RegionSize;
@@ -152,7 +182,7 @@ pub trait WizWalkerMemoryReader {
     
         if read_result == 0 {
             unsafe { CloseHandle(handle) };
-            return Err(anyhow!("Failed to read process memory"));
+            return Err(WizWalkerError::ReadFailed { address, size: mbi.RegionSize });
         }
     
         // Search for the pattern using regex
@@ -209,11 +239,9 @@ pub trait WizWalkerMemoryReader {
 
     fn pattern_scan(&mut self, pattern: &str, module_name_opt: Option<&str>, return_multiple: bool) -> Result<Vec<usize>> {
         let found_addresses = if let Some(module_name) = module_name_opt {
-            let module_obj = match module_from_name(self.process() as u32, module_name) {
+            let module_obj = match module_from_name(unsafe { GetProcessId(self.process()) }, module_name) {
                 Some(module) => module,
-                None => return Err(
-                    anyhow!("\"{module_name}\" module not found.")
-                )
+                None => return Err(WizWalkerError::ModuleNotFound { module: module_name.to_string() })
             };
 
             // let ptr = self.process();
@@ -224,11 +252,11 @@ pub trait WizWalkerMemoryReader {
         };
 
         if found_addresses.is_empty() {
-            return Err(anyhow!("Pattern \"{pattern}\" failed. You most likely need to restart the client."))
+            return Err(WizWalkerError::PatternNotFound { pattern: pattern.to_string() })
         }
 
         if found_addresses.len() > 1 && !return_multiple {
-            return Err(anyhow!("Get {} results for {pattern}", found_addresses.len()))
+            return Err(WizWalkerError::PatternNotUnique { pattern: pattern.to_string(), count: found_addresses.len() })
         }
 
         if return_multiple {
@@ -250,41 +278,46 @@ pub trait WizWalkerMemoryReader {
 
         let file_path = PathBuf::from(module_dir).join(PathBuf::from(module_name));
         if !file_path.exists() {
-            return Err(anyhow!("No module named {module_name}"))
+            return Err(WizWalkerError::ModuleNotFound { module: module_name.to_string() })
         }
 
         let file_path_str = match file_path.to_str() {
             Some(f) => f,
-            None => return Err(anyhow!("Could not convert PathBuf \"{:#?}\"", file_path))
+            None => return Err(WizWalkerError::ModuleNotFound { module: file_path.to_string_lossy().into_owned() })
         };
 
         let symbols = self.get_symbols(file_path_str, force_reload)?;
 
         let symbol = match symbols.get(symbol_name) {
             Some(s) => s,
-            None => return Err(anyhow!("No symbol named \"{symbol_name}\" in module \"{module_name}\""))
+            None => return Err(WizWalkerError::SymbolNotFound { module: module_name.to_string(), symbol: symbol_name.to_string() })
         };
 
-        let module = match module_from_name(self.process() as u32, module_name) {
+        let module = match module_from_name(unsafe { GetProcessId(self.process()) }, module_name) {
             Some(module_obj) => module_obj,
-            None => return Err(anyhow!("Could not get module \"{module_name}\" despite being in symbol table."))
+            None => return Err(WizWalkerError::ModuleNotFound { module: module_name.to_string() })
         };
 
         return Ok(module.modBaseAddr as usize + *symbol as usize)
     }
 
     fn allocate(&mut self, size: usize) -> Result<usize> {
+        self.allocate_with_protection(size, PAGE_READWRITE)
+    }
+
+
+    fn allocate_with_protection(&mut self, size: usize, protection: u32) -> Result<usize> {
         let result = unsafe {
             let allocated = VirtualAllocEx(
                 self.process(),
                 null_mut(),
                 size,
                 MEM_COMMIT | MEM_RESERVE,
-                 PAGE_READWRITE
+                protection
             );
 
             if allocated.is_null() {
-                return Err(anyhow!("Failed to allocate memory"))
+                return Err(WizWalkerError::AllocationFailed { size })
             }
 
             allocated as usize
@@ -294,6 +327,86 @@ pub trait WizWalkerMemoryReader {
     }
 
 
+    fn query_region(&mut self, address: usize) -> Result<MEMORY_BASIC_INFORMATION> {
+        let mut mbi: MEMORY_BASIC_INFORMATION = unsafe {
+            std::mem::zeroed()
+        };
+
+        let result = unsafe {
+            VirtualQueryEx(
+                self.process(),
+                address as *const _,
+                &mut mbi,
+                std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+            )
+        };
+
+        if result == 0 {
+            return Err(WizWalkerError::QueryFailed { address });
+        }
+
+        Ok(mbi)
+    }
+
+
+    /// Allocates `size` bytes within `NEAR_ALLOCATION_RANGE` of `address`.
+    ///
+    /// Free regions around the target are tried closest first, so a `jmp rel32`
+    /// from `address` can reach the allocation.
+    fn allocate_near(&mut self, address: usize, size: usize, protection: u32) -> Result<usize> {
+        let mut system_info: SYSTEM_INFO = unsafe { std::mem::zeroed() };
+        unsafe { GetSystemInfo(&mut system_info) };
+
+        let granularity = system_info.dwAllocationGranularity as usize;
+        let low = address.saturating_sub(NEAR_ALLOCATION_RANGE).max(system_info.lpMinimumApplicationAddress as usize);
+        let high = address.saturating_add(NEAR_ALLOCATION_RANGE).min(system_info.lpMaximumApplicationAddress as usize);
+
+        let mut candidates: Vec<usize> = Vec::new();
+        let mut region_address = low;
+
+        while region_address < high {
+            let mbi = self.query_region(region_address)?;
+            let region_start = (mbi.BaseAddress as usize).max(low);
+            let region_end = (mbi.BaseAddress as usize + mbi.RegionSize).min(high);
+
+            if mbi.State == MEM_FREE && region_end > region_start + size {
+                // Both ends of a free region, whichever is closer to the target wins.
+                let first = region_start.next_multiple_of(granularity);
+                let last = (region_end - size) / granularity * granularity;
+
+                if first + size <= region_end {
+                    candidates.push(first);
+                }
+                if last > first {
+                    candidates.push(last);
+                }
+            }
+
+            region_address = mbi.BaseAddress as usize + mbi.RegionSize;
+        }
+
+        candidates.sort_by_key(|candidate| candidate.abs_diff(address));
+
+        for candidate in candidates {
+            let allocated = unsafe {
+                VirtualAllocEx(
+                    self.process(),
+                    candidate as *mut c_void,
+                    size,
+                    MEM_COMMIT | MEM_RESERVE,
+                    protection
+                )
+            };
+
+            if !allocated.is_null() {
+                return Ok(allocated as usize)
+            }
+        }
+
+        Err(WizWalkerError::AllocationFailed { size })
+    }
+
+
     fn free(&mut self, address: usize) -> Result<()> {
         let result = unsafe {
             VirtualFreeEx(
@@ -305,7 +418,7 @@ pub trait WizWalkerMemoryReader {
         };
 
         if result == 0 {
-            return Err(anyhow!("Failed to free memory at address {:#x}", address))
+            return Err(WizWalkerError::FreeFailed { address })
         }
 
         Ok(())
@@ -327,7 +440,7 @@ pub trait WizWalkerMemoryReader {
         };
     
         if thread_handle.is_null() || thread_handle == INVALID_HANDLE_VALUE {
-            return Err(anyhow!("Failed to create thread"))
+            return Err(WizWalkerError::ThreadCreationFailed { address })
         }
 
         Ok(())
@@ -335,7 +448,7 @@ pub trait WizWalkerMemoryReader {
 
     fn read_bytes(&mut self, address: usize, size: usize) -> Result<Vec<u8>> {
         if !(0 < address && address <= 0x7FFFFFFFFFFFFFFF) {
-            return Err(anyhow!("Address \"{:#x}\" out of bounds", address))
+            return Err(WizWalkerError::AddressOutOfBounds { address })
         }
 
         let mut buffer = vec![0u8; size];
@@ -350,12 +463,12 @@ pub trait WizWalkerMemoryReader {
             &mut bytes_read)
         };
 
-        if !self.is_running()? && result == 0 {
-            return Err(anyhow!("Client must be running to perform this action."))
+        if result == 0 && !self.is_running()? {
+            return Err(WizWalkerError::ClientClosed)
         }
 
         if result == 0 {
-            return Err(anyhow!("Unable to read memory at address \"{:#x}\".", address))
+            return Err(WizWalkerError::ReadFailed { address, size })
         }
 
         return Ok(buffer)
@@ -376,12 +489,94 @@ pub trait WizWalkerMemoryReader {
             )
         };
 
-        if !self.is_running()? && result == 0 {
-            return Err(anyhow!("Client must be running to perform this action."))
+        if result == 0 && !self.is_running()? {
+            return Err(WizWalkerError::ClientClosed)
+        }
+
+        if result == 0 {
+            return Err(WizWalkerError::WriteFailed { address, size })
+        }
+
+        Ok(())
+    }
+
+
+    /// Changes the protection of the pages spanning `address..address + size`, returning the previous protection.
+    fn protect(&mut self, address: usize, size: usize, protection: u32) -> Result<u32> {
+        let mut old_protection: u32 = 0;
+
+        let result = unsafe {
+            VirtualProtectEx(
+                self.process(),
+                address as *mut c_void,
+                size,
+                protection,
+                &mut old_protection,
+            )
+        };
+
+        if result == 0 {
+            return Err(WizWalkerError::ProtectFailed { address, protection })
         }
 
+        Ok(old_protection)
+    }
+
+
+    fn flush_instruction_cache(&mut self, address: usize, size: usize) -> Result<()> {
+        let result = unsafe {
+            FlushInstructionCache(self.process(), address as *const c_void, size)
+        };
+
         if result == 0 {
-            return Err(anyhow!("Unable to write memory at address \"{:#x}\".", address))
+            return Err(WizWalkerError::FlushFailed { address })
+        }
+
+        Ok(())
+    }
+
+
+    /// Writes `value`, temporarily making the pages writable if needed.
+    ///
+    /// The original protection is always restored, the written bytes are read
+    /// back and compared, and the instruction cache is flushed when the target
+    /// is executable.
+    fn write_bytes_guarded(&mut self, address: usize, value: Vec<u8>) -> Result<()> {
+        let size = value.len();
+        if size == 0 {
+            return Ok(())
+        }
+
+        // `VirtualProtectEx` only reports the first page's old protection, so
+        // pages are changed and restored one at a time.
+        let mut old_protections: Vec<(usize, u32)> = Vec::new();
+        for page in (page_of(address)..address + size).step_by(PAGE_SIZE) {
+            match self.protect(page, PAGE_SIZE, PAGE_EXECUTE_READWRITE) {
+                Ok(old_protection) => old_protections.push((page, old_protection)),
+                Err(error) => {
+                    restore_protections(self, &old_protections)?;
+                    return Err(error)
+                }
+            }
+        }
+
+        let write_result = self.write_bytes(address, value.clone());
+        let restore_result = restore_protections(self, &old_protections);
+
+        write_result?;
+        restore_result?;
+
+        let written = self.read_bytes(address, size)?;
+        if written != value {
+            return Err(WizWalkerError::WriteVerificationFailed { address, size })
+        }
+
+        let executable = [PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY];
+        let any_executable = old_protections.iter()
+            .any(|(_, old_protection)| executable.iter().any(|protection| old_protection & protection != 0));
+
+        if any_executable {
+            self.flush_instruction_cache(address, size)?;
         }
 
         Ok(())
@@ -409,4 +604,335 @@ pub trait WizWalkerMemoryReader {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+
+    /// Reads a whole `remote_struct!` layout with a single read.
+    fn read_struct<T: RemoteStruct>(&mut self, address: usize) -> Result<T> {
+        let data_bytes = self.read_bytes(address, T::SIZE)?;
+
+        T::from_remote_bytes(&data_bytes)
+    }
+
+
+    /// Reads `count` structs laid out `stride` bytes apart with a single read.
+    fn read_structs<T: RemoteStruct>(&mut self, address: usize, count: usize, stride: usize) -> Result<Vec<T>> {
+        if count == 0 {
+            return Ok(Vec::new())
+        }
+
+        if stride < T::SIZE {
+            return Err(WizWalkerError::InvalidData {
+                address,
+                reason: format!("stride {stride} is smaller than the struct size {}", T::SIZE),
+            })
+        }
+
+        let data_bytes = self.read_bytes(address, stride * (count - 1) + T::SIZE)?;
+
+        (0..count)
+            .map(|index| T::from_remote_bytes(&data_bytes[index * stride..]))
+            .collect()
+    }
+
+
+    /// Reads a C string, stopping at the first null byte or after `max_size` bytes.
+    fn read_null_terminated_string(&mut self, address: usize, max_size: usize) -> Result<String> {
+        let data_bytes = self.read_bytes(address, max_size)?;
+        let end = data_bytes.iter().position(|byte| *byte == 0).unwrap_or(data_bytes.len());
+
+        Ok(String::from_utf8_lossy(&data_bytes[..end]).into_owned())
+    }
+
+
+    fn read_string_chars(&mut self, address: usize, char_size: usize) -> Result<Vec<u8>> {
+        let layout: StdStringLayout = self.read_struct(address)?;
+        let size = layout.size as usize;
+
+        if size > MAX_CONTAINER_SIZE {
+            return Err(WizWalkerError::InvalidData { address, reason: format!("string has an invalid size of {size}") })
+        }
+
+        let byte_size = size * char_size;
+
+        if layout.is_inline(char_size) {
+            return match layout.buffer.get(..byte_size) {
+                Some(chars) => Ok(chars.to_vec()),
+                None => Err(WizWalkerError::InvalidData { address, reason: "inline string is larger than its buffer".to_string() })
+            }
+        }
+
+        if byte_size == 0 {
+            return Ok(Vec::new())
+        }
+
+        self.read_bytes(layout.heap_pointer(), byte_size)
+    }
+
+
+    /// Reads an MSVC `std::string`, handling the small string optimization.
+    fn read_std_string(&mut self, address: usize) -> Result<String> {
+        let chars = self.read_string_chars(address, 1)?;
+
+        Ok(String::from_utf8_lossy(&chars).into_owned())
+    }
+
+
+    /// Reads an MSVC `std::wstring` (UTF-16).
+    fn read_std_wstring(&mut self, address: usize) -> Result<String> {
+        let chars = self.read_string_chars(address, 2)?;
+        let wide: Vec<u16> = chars.chunks_exact(2).map(pod_read_unaligned).collect();
+
+        Ok(String::from_utf16_lossy(&wide))
+    }
+
+
+    fn write_string_chars(&mut self, address: usize, mut chars: Vec<u8>, char_size: usize) -> Result<()> {
+        let layout: StdStringLayout = self.read_struct(address)?;
+        let size = chars.len() / char_size;
+
+        // The game owns the string's allocation, so we can only write in place.
+        if size > layout.capacity as usize {
+            return Err(WizWalkerError::InvalidData {
+                address,
+                reason: format!("string of length {size} does not fit the capacity {}", layout.capacity),
+            })
+        }
+
+        let data_address = match layout.is_inline(char_size) {
+            true => address,
+            false => layout.heap_pointer(),
+        };
+
+        chars.extend(std::iter::repeat_n(0, char_size));
+
+        self.write_bytes(data_address, chars)?;
+        self.write_typed(address + 0x10, size as u64)
+    }
+
+
+    /// Overwrites an MSVC `std::string` in place. Fails if `value` exceeds the string's capacity.
+    fn write_std_string(&mut self, address: usize, value: &str) -> Result<()> {
+        self.write_string_chars(address, value.as_bytes().to_vec(), 1)
+    }
+
+
+    /// Overwrites an MSVC `std::wstring` in place. Fails if `value` exceeds the string's capacity.
+    fn write_std_wstring(&mut self, address: usize, value: &str) -> Result<()> {
+        let chars = value.encode_utf16().flat_map(u16::to_le_bytes).collect();
+
+        self.write_string_chars(address, chars, 2)
+    }
+
+
+    /// Reads every element of an MSVC `std::vector<T>`.
+    fn read_std_vector<T: Pod>(&mut self, address: usize) -> Result<Vec<T>> {
+        let layout: StdVectorLayout = self.read_struct(address)?;
+        let element_size = std::mem::size_of::<T>();
+        let byte_len = layout.byte_len();
+
+        if byte_len == 0 || element_size == 0 {
+            return Ok(Vec::new())
+        }
+
+        if !byte_len.is_multiple_of(element_size) || byte_len / element_size > MAX_CONTAINER_SIZE {
+            return Err(WizWalkerError::InvalidData { address, reason: format!("vector has an invalid size of {byte_len} bytes") })
+        }
+
+        let data_bytes = self.read_bytes(layout.first as usize, byte_len)?;
+
+        Ok(data_bytes.chunks_exact(element_size).map(pod_read_unaligned).collect())
+    }
+
+
+    /// Reads a `std::vector<SharedPointer<T>>`, returning the non-null object addresses.
+    fn read_shared_vector(&mut self, address: usize) -> Result<Vec<usize>> {
+        let shared_pointers: Vec<[u64; 2]> = self.read_std_vector(address)?;
+
+        Ok(shared_pointers.into_iter()
+            .filter(|[pointer, _]| *pointer != 0)
+            .map(|[pointer, _]| pointer as usize)
+            .collect())
+    }
+
+
+    /// Returns the address of each value stored in an MSVC `std::list`, in order.
+    fn read_std_list_nodes(&mut self, address: usize) -> Result<Vec<usize>> {
+        let layout: StdNodeContainerLayout = self.read_struct(address)?;
+        let size = layout.size as usize;
+
+        if size > MAX_CONTAINER_SIZE {
+            return Err(WizWalkerError::InvalidData { address, reason: format!("list has an invalid size of {size}") })
+        }
+
+        let head = layout.head as usize;
+        let mut values = Vec::with_capacity(size);
+        let mut node = self.read_struct::<StdListNodeLayout>(head)?.next as usize;
+
+        while node != head && values.len() < size {
+            if node == 0 {
+                return Err(WizWalkerError::NullPointer { address })
+            }
+
+            values.push(node + STD_LIST_NODE_VALUE_OFFSET);
+            node = self.read_struct::<StdListNodeLayout>(node)?.next as usize;
+        }
+
+        Ok(values)
+    }
+
+
+    /// Reads every element of an MSVC `std::list<T>`.
+    fn read_std_list<T: Pod>(&mut self, address: usize) -> Result<Vec<T>> {
+        self.read_std_list_nodes(address)?
+            .into_iter()
+            .map(|value_address| self.read_typed(value_address))
+            .collect()
+    }
+
+
+    /// Reads a `std::list<SharedPointer<T>>`, returning the non-null object addresses.
+    fn read_shared_list(&mut self, address: usize) -> Result<Vec<usize>> {
+        let shared_pointers: Vec<SharedPointer> = self.read_std_list_nodes(address)?
+            .into_iter()
+            .map(|value_address| self.read_struct(value_address))
+            .collect::<Result<_>>()?;
+
+        Ok(shared_pointers.into_iter()
+            .filter(|pointer| !pointer.is_null())
+            .map(|pointer| pointer.pointer as usize)
+            .collect())
+    }
+
+
+    /// Returns the address of each key/value pair stored in an MSVC `std::map`, in key order.
+    ///
+    /// Where the pair starts in a node depends on its alignment, `pair_align`;
+    /// see `std_pair_align`.
+    fn read_std_map_nodes(&mut self, address: usize, pair_align: usize) -> Result<Vec<usize>> {
+        let pair_offset = std_map_node_pair_offset(pair_align);
+        let layout: StdNodeContainerLayout = self.read_struct(address)?;
+        let size = layout.size as usize;
+
+        if size > MAX_CONTAINER_SIZE {
+            return Err(WizWalkerError::InvalidData { address, reason: format!("map has an invalid size of {size}") })
+        }
+
+        let head: StdMapNodeLayout = self.read_struct(layout.head as usize)?;
+        let mut pairs = Vec::with_capacity(size);
+        let mut stack: Vec<usize> = Vec::new();
+        let mut node = head.parent as usize;
+
+        // Iterative in-order walk; the head sentinel and leaves are marked with `is_nil`.
+        loop {
+            while node != 0 && node != layout.head as usize {
+                let current: StdMapNodeLayout = self.read_struct(node)?;
+                if current.is_nil != 0 {
+                    break
+                }
+
+                stack.push(node);
+                node = current.left as usize;
+            }
+
+            let next = match stack.pop() {
+                Some(next) => next,
+                None => break
+            };
+
+            pairs.push(next + pair_offset);
+            if pairs.len() > size {
+                return Err(WizWalkerError::InvalidData { address, reason: format!("map has more nodes than its size of {size}") })
+            }
+
+            node = self.read_struct::<StdMapNodeLayout>(next)?.right as usize;
+        }
+
+        Ok(pairs)
+    }
+
+
+    /// Reads every entry of an MSVC `std::map<K, V>`, in key order.
+    fn read_std_map<K: Pod, V: Pod>(&mut self, address: usize) -> Result<Vec<(K, V)>> {
+        let value_offset = std_pair_value_offset::<K, V>();
+
+        self.read_std_map_nodes(address, std_pair_align::<K, V>())?
+            .into_iter()
+            .map(|pair_address| {
+                Ok((self.read_typed(pair_address)?, self.read_typed(pair_address + value_offset)?))
+            })
+            .collect()
+    }
+
+
+    fn read_shared_pointer(&mut self, address: usize) -> Result<SharedPointer> {
+        self.read_struct(address)
+    }
+}
+
+
+/// Implements the process-touching `WizWalkerMemoryReader` methods by forwarding them to `self.$field`.
+///
+/// Everything built on top of them (typed reads, containers, guarded writes)
+/// keeps its default implementation and so runs against the wrapped backend.
+/// Expand it inside an `impl WizWalkerMemoryReader for ...` block.
+macro_rules! delegate_memory_reader {
+    ($field:ident) => {
+        fn process(&self) -> ::winapi::um::winnt::HANDLE {
+            self.$field.process()
+        }
+
+        fn symbol_table(&mut self) -> &mut ::std::collections::HashMap<String, ::std::collections::HashMap<String, u32>> {
+            self.$field.symbol_table()
+        }
+
+        fn is_running(&self) -> $crate::error::Result<bool> {
+            self.$field.is_running()
+        }
+
+        fn pattern_scan(&mut self, pattern: &str, module_name_opt: Option<&str>, return_multiple: bool) -> $crate::error::Result<Vec<usize>> {
+            self.$field.pattern_scan(pattern, module_name_opt, return_multiple)
+        }
+
+        fn get_address_from_symbol(&mut self, module_name: &str, symbol_name: &str, module_dir_opt: Option<&str>, force_reload: bool) -> $crate::error::Result<usize> {
+            self.$field.get_address_from_symbol(module_name, symbol_name, module_dir_opt, force_reload)
+        }
+
+        fn allocate_with_protection(&mut self, size: usize, protection: u32) -> $crate::error::Result<usize> {
+            self.$field.allocate_with_protection(size, protection)
+        }
+
+        fn query_region(&mut self, address: usize) -> $crate::error::Result<::winapi::um::winnt::MEMORY_BASIC_INFORMATION> {
+            self.$field.query_region(address)
+        }
+
+        fn allocate_near(&mut self, address: usize, size: usize, protection: u32) -> $crate::error::Result<usize> {
+            self.$field.allocate_near(address, size, protection)
+        }
+
+        fn free(&mut self, address: usize) -> $crate::error::Result<()> {
+            self.$field.free(address)
+        }
+
+        fn start_thread(&mut self, address: usize) -> $crate::error::Result<()> {
+            self.$field.start_thread(address)
+        }
+
+        fn read_bytes(&mut self, address: usize, size: usize) -> $crate::error::Result<Vec<u8>> {
+            self.$field.read_bytes(address, size)
+        }
+
+        fn write_bytes(&mut self, address: usize, value: Vec<u8>) -> $crate::error::Result<()> {
+            self.$field.write_bytes(address, value)
+        }
+
+        fn protect(&mut self, address: usize, size: usize, protection: u32) -> $crate::error::Result<u32> {
+            self.$field.protect(address, size, protection)
+        }
+
+        fn flush_instruction_cache(&mut self, address: usize, size: usize) -> $crate::error::Result<()> {
+            self.$field.flush_instruction_cache(address, size)
+        }
+    };
+}
+
+pub(crate) use delegate_memory_reader;
@@ -1,4 +1,18 @@
 pub mod memory_reader;
+pub mod memory_object;
 pub mod memory_objects;
 pub mod handler;
-pub mod hooks;
\ No newline at end of file
+pub mod hooks;
+pub mod pointers;
+pub mod containers;
+pub mod read_cache;
+pub mod patch;
+pub mod assembler;
+pub mod allocator;
+pub mod signatures;
+pub mod type_dump;
+pub mod reflection;
+pub mod registry;
+
+#[cfg(test)]
+pub(crate) mod fake_memory;
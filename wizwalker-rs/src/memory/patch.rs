@@ -0,0 +1,141 @@
+use crate::error::{Result, WizWalkerError};
+use winapi::um::winnt::HANDLE;
+
+use std::collections::HashMap;
+
+use super::memory_reader::WizWalkerMemoryReader;
+
+
+/// A single modification made through a `PatchJournal`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    pub address: usize,
+    pub original: Vec<u8>,
+    pub patched: Vec<u8>,
+}
+
+
+/// Records every patch written to the client so they can all be undone.
+///
+/// Patches are written with `write_bytes_guarded`, so they work on code pages
+/// and are verified. A failed patch restores whatever it partially wrote.
+/// Dropping the journal rolls every patch back, newest first, unless
+/// `set_rollback_on_drop(false)` was called.
+pub struct PatchJournal {
+    process: HANDLE,
+    symbol_table: HashMap<String, HashMap<String, u32>>,
+    patches: Vec<Patch>,
+    rollback_on_drop: bool,
+}
+
+impl PatchJournal {
+    pub fn new(process: HANDLE) -> Self {
+        Self {
+            process,
+            symbol_table: HashMap::new(),
+            patches: Vec::new(),
+            rollback_on_drop: true,
+        }
+    }
+
+    pub fn patches(&self) -> &[Patch] {
+        &self.patches
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    pub fn set_rollback_on_drop(&mut self, rollback_on_drop: bool) {
+        self.rollback_on_drop = rollback_on_drop;
+    }
+
+    /// Writes `bytes` at `address` and records the bytes it replaced.
+    pub fn patch(&mut self, address: usize, bytes: Vec<u8>) -> Result<()> {
+        let original = self.read_bytes(address, bytes.len())?;
+
+        if let Err(error) = self.write_bytes_guarded(address, bytes.clone()) {
+            // The write may have partially succeeded; put the original bytes back.
+            if let Err(restore_error) = self.write_bytes_guarded(address, original) {
+                log::error!("Failed to restore \"{:#x}\" after a failed patch: {restore_error}", address);
+            }
+
+            return Err(error)
+        }
+
+        self.patches.push(Patch {
+            address,
+            original,
+            patched: bytes,
+        });
+
+        Ok(())
+    }
+
+    /// Undoes the most recent patch.
+    pub fn revert_last(&mut self) -> Result<Option<Patch>> {
+        let patch = match self.patches.pop() {
+            Some(patch) => patch,
+            None => return Ok(None)
+        };
+
+        if let Err(error) = self.write_bytes_guarded(patch.address, patch.original.clone()) {
+            self.patches.push(patch);
+            return Err(error)
+        }
+
+        Ok(Some(patch))
+    }
+
+    /// Undoes every recorded patch, newest first.
+    ///
+    /// Keeps going past failures so one bad patch cannot leave the others in place;
+    /// patches that could not be reverted stay in the journal.
+    pub fn rollback(&mut self) -> Result<()> {
+        let mut failed: Vec<Patch> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+
+        while let Some(patch) = self.patches.pop() {
+            if let Err(error) = self.write_bytes_guarded(patch.address, patch.original.clone()) {
+                errors.push(format!("{:#x}: {error}", patch.address));
+                failed.push(patch);
+            }
+        }
+
+        failed.reverse();
+        self.patches = failed;
+
+        if !errors.is_empty() {
+            return Err(WizWalkerError::RollbackFailed { count: errors.len(), details: errors.join("; ") })
+        }
+
+        Ok(())
+    }
+
+    /// Forgets every patch without reverting it, leaving the client modified.
+    pub fn clear(&mut self) -> Vec<Patch> {
+        std::mem::take(&mut self.patches)
+    }
+}
+
+impl WizWalkerMemoryReader for PatchJournal {
+    fn process(&self) -> HANDLE {
+        self.process
+    }
+
+    fn symbol_table(&mut self) -> &mut HashMap<String, HashMap<String, u32>> {
+        &mut self.symbol_table
+    }
+}
+
+impl Drop for PatchJournal {
+    fn drop(&mut self) {
+        if !self.rollback_on_drop || self.patches.is_empty() {
+            return
+        }
+
+        if let Err(error) = self.rollback() {
+            log::error!("Patch journal rollback on drop failed: {error}");
+        }
+    }
+}
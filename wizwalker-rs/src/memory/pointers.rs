@@ -0,0 +1,284 @@
+use crate::error::{Result, WizWalkerError};
+use bytemuck::{pod_read_unaligned, Pod, Zeroable};
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use super::memory_reader::WizWalkerMemoryReader;
+
+
+/// A typed address in the remote process.
+///
+/// `RemotePtr` has the same layout as a raw 64 bit pointer, so it can be read
+/// out of game memory directly or used as a field of a `remote_struct!`.
+#[repr(transparent)]
+pub struct RemotePtr<T> {
+    address: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> RemotePtr<T> {
+    pub const fn new(address: usize) -> Self {
+        Self {
+            address,
+            _marker: PhantomData,
+        }
+    }
+
+    pub const fn null() -> Self {
+        Self::new(0)
+    }
+
+    pub fn address(&self) -> usize {
+        self.address
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.address == 0
+    }
+
+    /// Reinterprets the pointee as another type, keeping the address.
+    pub fn cast<U>(&self) -> RemotePtr<U> {
+        RemotePtr::new(self.address)
+    }
+
+    /// Moves the pointer by a raw byte offset, e.g. to reach a field.
+    pub fn byte_offset<U>(&self, offset: usize) -> RemotePtr<U> {
+        RemotePtr::new(self.address + offset)
+    }
+
+    fn non_null(&self) -> Result<usize> {
+        if self.is_null() {
+            return Err(WizWalkerError::NullDereference { type_name: std::any::type_name::<T>() })
+        }
+
+        Ok(self.address)
+    }
+}
+
+impl<T: Pod> RemotePtr<T> {
+    /// Moves the pointer by `count` elements of `T`, like `pointer::add`.
+    pub fn add(&self, count: usize) -> Self {
+        Self::new(self.address + count * std::mem::size_of::<T>())
+    }
+
+    pub fn read<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<T> {
+        reader.read_typed(self.non_null()?)
+    }
+
+    pub fn write<R: WizWalkerMemoryReader>(&self, reader: &mut R, value: T) -> Result<()>
+    where
+        T: Send,
+    {
+        reader.write_typed(self.non_null()?, value)
+    }
+
+    /// Reads `count` consecutive values with a single read.
+    pub fn read_array<R: WizWalkerMemoryReader>(&self, reader: &mut R, count: usize) -> Result<Vec<T>> {
+        let size = std::mem::size_of::<T>();
+        let bytes = reader.read_bytes(self.non_null()?, count * size)?;
+
+        Ok(bytes.chunks_exact(size).map(pod_read_unaligned).collect())
+    }
+}
+
+impl<T: RemoteStruct> RemotePtr<T> {
+    pub fn read_struct<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<T> {
+        reader.read_struct(self.non_null()?)
+    }
+}
+
+impl<T> Clone for RemotePtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RemotePtr<T> {}
+
+impl<T> PartialEq for RemotePtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl<T> Eq for RemotePtr<T> {}
+
+impl<T> Default for RemotePtr<T> {
+    fn default() -> Self {
+        Self::null()
+    }
+}
+
+impl<T> Debug for RemotePtr<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<RemotePtr<{}> {:#x}>", std::any::type_name::<T>(), self.address)
+    }
+}
+
+// SAFETY: `RemotePtr` is a transparent wrapper over `usize`; every bit pattern
+// is a valid (if possibly dangling) remote address and nothing is dereferenced locally.
+unsafe impl<T> Zeroable for RemotePtr<T> {}
+unsafe impl<T: 'static> Pod for RemotePtr<T> {}
+
+
+/// A chain of offsets followed from a base address, e.g. `base + 0x10 -> +0x98 -> +0x2C`.
+///
+/// Every offset but the last is added and then dereferenced as a pointer; the
+/// last offset is only added, giving the address of the final value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointerChain {
+    base: usize,
+    offsets: Vec<usize>,
+}
+
+impl PointerChain {
+    pub fn new(base: usize, offsets: &[usize]) -> Self {
+        Self {
+            base,
+            offsets: offsets.to_vec(),
+        }
+    }
+
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// Appends another offset to the chain.
+    pub fn then(mut self, offset: usize) -> Self {
+        self.offsets.push(offset);
+        self
+    }
+
+    /// Follows the chain and returns the final address.
+    ///
+    /// Fails if any intermediate pointer is null instead of reading from low memory.
+    pub fn resolve<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<usize> {
+        if self.base == 0 {
+            return Err(WizWalkerError::NullPointerInChain { slot: 0, depth: 0, offset: 0 })
+        }
+
+        let (last, derefs) = match self.offsets.split_last() {
+            Some(split) => split,
+            None => return Ok(self.base)
+        };
+
+        let mut address = self.base;
+        for (depth, offset) in derefs.iter().enumerate() {
+            let pointer_address = address + offset;
+            address = reader.read_typed::<u64>(pointer_address)? as usize;
+
+            if address == 0 {
+                return Err(WizWalkerError::NullPointerInChain { slot: pointer_address, depth: depth + 1, offset: *offset })
+            }
+        }
+
+        Ok(address + last)
+    }
+
+    pub fn resolve_ptr<T, R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<RemotePtr<T>> {
+        Ok(RemotePtr::new(self.resolve(reader)?))
+    }
+
+    pub fn read<T: Pod, R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<T> {
+        let address = self.resolve(reader)?;
+        reader.read_typed(address)
+    }
+
+    pub fn write<T: Pod + Send, R: WizWalkerMemoryReader>(&self, reader: &mut R, value: T) -> Result<()> {
+        let address = self.resolve(reader)?;
+        reader.write_typed(address, value)
+    }
+}
+
+
+/// A struct whose fields live at fixed offsets in remote memory.
+///
+/// Implemented by `remote_struct!`; the whole struct is fetched with one
+/// `read_bytes` call of `SIZE` bytes and then decoded field by field.
+pub trait RemoteStruct: Sized {
+    /// Number of bytes spanned by the declared fields, starting from the base address.
+    const SIZE: usize;
+
+    fn from_remote_bytes(bytes: &[u8]) -> Result<Self>;
+}
+
+
+/// Decodes a single field out of a buffer read from remote memory.
+///
+/// Used by `remote_struct!`, but also handy for hand written layouts.
+pub fn read_field<T: Pod>(bytes: &[u8], offset: usize) -> Result<T> {
+    let end = offset + std::mem::size_of::<T>();
+
+    match bytes.get(offset..end) {
+        Some(field_bytes) => Ok(pod_read_unaligned(field_bytes)),
+        None => Err(WizWalkerError::InvalidData {
+            address: offset,
+            reason: format!("field of type {} does not fit in {} bytes", std::any::type_name::<T>(), bytes.len()),
+        })
+    }
+}
+
+
+/// Declares a remote struct layout with explicit field offsets.
+///
+/// ```ignore
+/// remote_struct! {
+///     #[derive(Debug, Clone, Copy)]
+///     pub struct ActorTransform {
+///         0x58 => pub x: f32,
+///         0x5C => pub y: f32,
+///         0x60 => pub z: f32,
+///         0x98 => pub parent: RemotePtr<u8>,
+///     }
+/// }
+///
+/// let transform: ActorTransform = reader.read_struct(address)?;
+/// ```
+///
+/// Fields must be `Pod`; gaps between offsets are skipped.
+#[macro_export]
+macro_rules! remote_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $offset:expr => $field_vis:vis $field:ident : $ty:ty
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $ty,
+            )+
+        }
+
+        impl $crate::memory::pointers::RemoteStruct for $name {
+            const SIZE: usize = {
+                let mut size = 0;
+                $(
+                    let end = $offset + ::std::mem::size_of::<$ty>();
+                    if end > size {
+                        size = end;
+                    }
+                )+
+                size
+            };
+
+            fn from_remote_bytes(bytes: &[u8]) -> $crate::error::Result<Self> {
+                Ok(Self {
+                    $(
+                        $field: $crate::memory::pointers::read_field::<$ty>(bytes, $offset)?,
+                    )+
+                })
+            }
+        }
+    };
+}
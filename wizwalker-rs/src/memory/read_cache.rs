@@ -0,0 +1,525 @@
+use crate::error::{Result, WizWalkerError};
+use bytemuck::{pod_read_unaligned, Pod};
+use winapi::um::winnt::HANDLE;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::memory_reader::WizWalkerMemoryReader;
+
+
+pub const PAGE_SIZE: usize = 0x1000;
+
+/// Reads closer together than this are merged into a single `ReadProcessMemory` call.
+pub const DEFAULT_MAX_GAP: usize = 0x100;
+
+/// Once the cache holds more pages than this, stale pages are dropped before fetching new ones.
+const MAX_CACHED_PAGES: usize = 0x1000;
+
+
+pub(crate) fn page_of(address: usize) -> usize {
+    address & !(PAGE_SIZE - 1)
+}
+
+
+/// Collects many small reads and performs them as a few coalesced reads.
+///
+/// Requests are sorted and merged whenever the gap between them is at most
+/// `max_gap` bytes, trading a little over-reading for far fewer syscalls.
+#[derive(Debug, Clone)]
+pub struct ReadBatch {
+    requests: Vec<(usize, usize)>,
+    max_gap: usize,
+}
+
+impl Default for ReadBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadBatch {
+    pub fn new() -> Self {
+        Self::with_max_gap(DEFAULT_MAX_GAP)
+    }
+
+    pub fn with_max_gap(max_gap: usize) -> Self {
+        Self {
+            requests: Vec::new(),
+            max_gap,
+        }
+    }
+
+    /// Queues a read and returns its index into the `BatchResult`.
+    pub fn add(&mut self, address: usize, size: usize) -> usize {
+        self.requests.push((address, size));
+        self.requests.len() - 1
+    }
+
+    pub fn add_typed<T: Pod>(&mut self, address: usize) -> usize {
+        self.add(address, std::mem::size_of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// The `(address, size)` ranges that will actually be read.
+    pub fn coalesce(&self) -> Vec<(usize, usize)> {
+        let mut sorted: Vec<(usize, usize)> = self.requests.iter()
+            .filter(|(_, size)| *size > 0)
+            .copied()
+            .collect();
+        sorted.sort_unstable();
+
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for (address, size) in sorted {
+            let end = address + size;
+
+            match ranges.last_mut() {
+                Some((range_start, range_size)) if address <= *range_start + *range_size + self.max_gap => {
+                    let range_end = (*range_start + *range_size).max(end);
+                    *range_size = range_end - *range_start;
+                },
+                _ => ranges.push((address, size))
+            }
+        }
+
+        ranges
+    }
+
+    pub fn execute<R: WizWalkerMemoryReader>(&self, reader: &mut R) -> Result<BatchResult> {
+        let regions = self.coalesce()
+            .into_iter()
+            .map(|(address, size)| Ok((address, reader.read_bytes(address, size)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(BatchResult {
+            requests: self.requests.clone(),
+            regions,
+        })
+    }
+}
+
+
+/// The bytes fetched by a `ReadBatch`, addressable by request index.
+#[derive(Debug, Clone)]
+pub struct BatchResult {
+    requests: Vec<(usize, usize)>,
+    regions: Vec<(usize, Vec<u8>)>,
+}
+
+impl BatchResult {
+    pub fn bytes(&self, index: usize) -> Result<&[u8]> {
+        let (address, size) = *self.requests.get(index)
+            .ok_or(WizWalkerError::InvalidData { address: 0, reason: format!("no read with index {index} in this batch") })?;
+
+        find_in_regions(&self.regions, address, size)
+    }
+
+    pub fn typed<T: Pod>(&self, index: usize) -> Result<T> {
+        let data_bytes = self.bytes(index)?;
+
+        if data_bytes.len() != std::mem::size_of::<T>() {
+            return Err(WizWalkerError::InvalidData {
+                address: self.requests[index].0,
+                reason: format!("read is {} bytes, expected {}", data_bytes.len(), std::mem::size_of::<T>()),
+            })
+        }
+
+        Ok(pod_read_unaligned(data_bytes))
+    }
+
+    pub fn regions(&self) -> &[(usize, Vec<u8>)] {
+        &self.regions
+    }
+}
+
+
+fn find_in_regions(regions: &[(usize, Vec<u8>)], address: usize, size: usize) -> Result<&[u8]> {
+    for (region_address, data) in regions {
+        if address >= *region_address && address + size <= region_address + data.len() {
+            let start = address - region_address;
+            return Ok(&data[start..start + size])
+        }
+    }
+
+    Err(WizWalkerError::NotCaptured { address, size })
+}
+
+
+/// Wraps a reader and serves `read_bytes` from whole cached pages.
+///
+/// Pages stay valid for `tick`; a polling loop that reads hundreds of fields
+/// per tick then costs one read per touched page instead of one per field.
+/// Adjacent missing pages are fetched together. Writes go straight through and
+/// invalidate the pages they touch.
+pub struct CachedMemoryReader<R: WizWalkerMemoryReader> {
+    inner: R,
+    tick: Duration,
+    pages: HashMap<usize, (Instant, Vec<u8>)>,
+}
+
+impl<R: WizWalkerMemoryReader> CachedMemoryReader<R> {
+    pub fn new(inner: R, tick: Duration) -> Self {
+        Self {
+            inner,
+            tick,
+            pages: HashMap::new(),
+        }
+    }
+
+    pub fn inner(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    pub fn tick(&self) -> Duration {
+        self.tick
+    }
+
+    /// Changes how long pages stay valid. A zero tick disables caching.
+    pub fn set_tick(&mut self, tick: Duration) {
+        self.tick = tick;
+        self.purge_stale();
+    }
+
+    /// Drops every cached page, e.g. at the start of a new polling tick.
+    pub fn invalidate(&mut self) {
+        self.pages.clear();
+    }
+
+    pub fn invalidate_range(&mut self, address: usize, size: usize) {
+        if size == 0 {
+            return
+        }
+
+        let mut page = page_of(address);
+        while page <= page_of(address + size - 1) {
+            self.pages.remove(&page);
+            page += PAGE_SIZE;
+        }
+    }
+
+    pub fn purge_stale(&mut self) {
+        let tick = self.tick;
+        self.pages.retain(|_, (fetched, _)| fetched.elapsed() < tick);
+    }
+
+    fn is_fresh(&self, page: usize) -> bool {
+        match self.pages.get(&page) {
+            Some((fetched, _)) => fetched.elapsed() < self.tick,
+            None => false
+        }
+    }
+
+    fn fetch_pages(&mut self, first_page: usize, last_page: usize) -> Result<()> {
+        if self.pages.len() > MAX_CACHED_PAGES {
+            self.purge_stale();
+        }
+
+        let mut page = first_page;
+        while page <= last_page {
+            if self.is_fresh(page) {
+                page += PAGE_SIZE;
+                continue
+            }
+
+            // Extend the run over every consecutive page that also needs fetching.
+            let run_start = page;
+            while page <= last_page && !self.is_fresh(page) {
+                page += PAGE_SIZE;
+            }
+
+            let data = self.inner.read_bytes(run_start, page - run_start)?;
+            let fetched = Instant::now();
+
+            for (index, chunk) in data.chunks(PAGE_SIZE).enumerate() {
+                self.pages.insert(run_start + index * PAGE_SIZE, (fetched, chunk.to_vec()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: WizWalkerMemoryReader> WizWalkerMemoryReader for CachedMemoryReader<R> {
+    fn process(&self) -> HANDLE {
+        self.inner.process()
+    }
+
+    fn symbol_table(&mut self) -> &mut HashMap<String, HashMap<String, u32>> {
+        self.inner.symbol_table()
+    }
+
+    fn read_bytes(&mut self, address: usize, size: usize) -> Result<Vec<u8>> {
+        if self.tick.is_zero() || size == 0 {
+            return self.inner.read_bytes(address, size)
+        }
+
+        let first_page = page_of(address);
+        let last_page = page_of(address + size - 1);
+
+        self.fetch_pages(first_page, last_page)?;
+
+        let mut buffer = Vec::with_capacity(size);
+        let mut page = first_page;
+        while page <= last_page {
+            let (_, data) = self.pages.get(&page)
+                .ok_or(WizWalkerError::NotCaptured { address: page, size: PAGE_SIZE })?;
+
+            let start = address.max(page) - page;
+            let end = (address + size).min(page + PAGE_SIZE) - page;
+            buffer.extend_from_slice(&data[start..end]);
+
+            page += PAGE_SIZE;
+        }
+
+        Ok(buffer)
+    }
+
+    fn write_bytes(&mut self, address: usize, value: Vec<u8>) -> Result<()> {
+        let size = value.len();
+        let result = self.inner.write_bytes(address, value);
+        self.invalidate_range(address, size);
+
+        result
+    }
+}
+
+
+/// A set of memory regions captured at (as close as possible to) the same moment.
+///
+/// The snapshot implements `WizWalkerMemoryReader`, so anything that reads
+/// through the trait (pointer chains, `remote_struct!` layouts, containers)
+/// can derive values from it without touching the live process. Reads
+/// outside the captured regions and all writes fail.
+pub struct MemorySnapshot {
+    process: HANDLE,
+    symbol_table: HashMap<String, HashMap<String, u32>>,
+    regions: Vec<(usize, Vec<u8>)>,
+    taken_at: Instant,
+}
+
+impl MemorySnapshot {
+    /// Reads every region in one coalesced batch.
+    pub fn capture<R: WizWalkerMemoryReader>(reader: &mut R, regions: &[(usize, usize)]) -> Result<Self> {
+        let mut batch = ReadBatch::new();
+        for (address, size) in regions {
+            batch.add(*address, *size);
+        }
+
+        let result = batch.execute(reader)?;
+
+        Ok(Self {
+            process: reader.process(),
+            symbol_table: HashMap::new(),
+            regions: result.regions,
+            taken_at: Instant::now(),
+        })
+    }
+
+    /// Captures repeatedly until two consecutive captures agree.
+    ///
+    /// Guards against the game updating a value between the reads of a batch
+    /// (e.g. position written but orientation not yet). Gives up with an error
+    /// after `max_attempts` captures; at least two are always taken, since
+    /// there is nothing to compare with fewer.
+    pub fn capture_consistent<R: WizWalkerMemoryReader>(reader: &mut R, regions: &[(usize, usize)], max_attempts: usize) -> Result<Self> {
+        let max_attempts = max_attempts.max(2);
+        let mut previous = Self::capture(reader, regions)?;
+
+        for _ in 1..max_attempts {
+            let current = Self::capture(reader, regions)?;
+            if current.regions == previous.regions {
+                return Ok(current)
+            }
+
+            previous = current;
+        }
+
+        Err(WizWalkerError::SnapshotUnstable { attempts: max_attempts })
+    }
+
+    pub fn taken_at(&self) -> Instant {
+        self.taken_at
+    }
+
+    pub fn age(&self) -> Duration {
+        self.taken_at.elapsed()
+    }
+
+    pub fn regions(&self) -> &[(usize, Vec<u8>)] {
+        &self.regions
+    }
+}
+
+impl WizWalkerMemoryReader for MemorySnapshot {
+    fn process(&self) -> HANDLE {
+        self.process
+    }
+
+    fn symbol_table(&mut self) -> &mut HashMap<String, HashMap<String, u32>> {
+        &mut self.symbol_table
+    }
+
+    fn read_bytes(&mut self, address: usize, size: usize) -> Result<Vec<u8>> {
+        Ok(find_in_regions(&self.regions, address, size)?.to_vec())
+    }
+
+    fn write_bytes(&mut self, address: usize, _value: Vec<u8>) -> Result<()> {
+        Err(WizWalkerError::ReadOnly { address })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::fake_memory::{FakeMemory, FAKE_MODULE_BASE};
+
+    const BASE: usize = FAKE_MODULE_BASE;
+
+    /// Bumps a counter at `BASE` before every read until it reaches `settle_at`.
+    struct ChangingMemory {
+        memory: FakeMemory,
+        reads: u32,
+        settle_at: u32,
+    }
+
+    impl WizWalkerMemoryReader for ChangingMemory {
+        fn process(&self) -> HANDLE {
+            self.memory.process()
+        }
+
+        fn symbol_table(&mut self) -> &mut HashMap<String, HashMap<String, u32>> {
+            self.memory.symbol_table()
+        }
+
+        fn read_bytes(&mut self, address: usize, size: usize) -> Result<Vec<u8>> {
+            self.reads += 1;
+            self.memory.write_typed(BASE, self.reads.min(self.settle_at))?;
+            self.memory.read_bytes(address, size)
+        }
+
+        fn write_bytes(&mut self, address: usize, value: Vec<u8>) -> Result<()> {
+            self.memory.write_bytes(address, value)
+        }
+    }
+
+    fn changing_memory(settle_at: u32) -> ChangingMemory {
+        ChangingMemory {
+            memory: FakeMemory::with_zeroed_regions(&[(BASE, 0x100)]),
+            reads: 0,
+            settle_at,
+        }
+    }
+
+    #[test]
+    fn batches_merge_overlapping_adjacent_and_nearby_reads() {
+        let mut batch = ReadBatch::with_max_gap(0x10);
+        batch.add(0x1000, 8);
+        batch.add(0x1004, 8);
+        batch.add(0x100C, 4);
+        batch.add(0x1020, 4);
+        batch.add(0x1035, 4);
+        batch.add(0x2000, 0);
+
+        assert_eq!(batch.coalesce(), [(0x1000, 0x24), (0x1035, 4)]);
+        assert_eq!(ReadBatch::with_max_gap(0).coalesce(), []);
+
+        let mut batch = ReadBatch::with_max_gap(0);
+        batch.add(0x1010, 4);
+        batch.add(0x1000, 0x20);
+        batch.add(0x1020, 4);
+        assert_eq!(batch.coalesce(), [(0x1000, 0x24)]);
+    }
+
+    #[test]
+    fn batch_results_are_read_by_request() {
+        let mut memory = FakeMemory::with_zeroed_regions(&[(BASE, 0x100)]);
+        memory.write_typed(BASE + 0x10, 7u32).unwrap();
+        memory.write_typed(BASE + 0x40, 9u64).unwrap();
+
+        let mut batch = ReadBatch::new();
+        let first = batch.add_typed::<u32>(BASE + 0x10);
+        let second = batch.add_typed::<u64>(BASE + 0x40);
+        let result = batch.execute(&mut memory).unwrap();
+
+        assert_eq!(result.regions().len(), 1);
+        assert_eq!(result.typed::<u32>(first).unwrap(), 7);
+        assert_eq!(result.typed::<u64>(second).unwrap(), 9);
+        assert!(result.typed::<u64>(first).is_err());
+    }
+
+    #[test]
+    fn cached_pages_are_served_until_invalidated() {
+        let mut memory = FakeMemory::with_zeroed_regions(&[(BASE, 2 * PAGE_SIZE)]);
+        let mut cached = CachedMemoryReader::new(memory.clone(), Duration::from_secs(60));
+
+        memory.write_typed(BASE + 0xFFE, 0x1111_1111u32).unwrap();
+        assert_eq!(cached.read_typed::<u32>(BASE + 0xFFE).unwrap(), 0x1111_1111);
+
+        // Changed behind the cache's back, so the cached pages are now stale.
+        memory.write_typed(BASE + 0xFFE, 0x2222_2222u32).unwrap();
+        assert_eq!(cached.read_typed::<u32>(BASE + 0xFFE).unwrap(), 0x1111_1111);
+
+        cached.invalidate_range(BASE + 0x1000, 1);
+        assert_eq!(cached.read_typed::<u32>(BASE + 0xFFE).unwrap(), 0x2222_1111);
+
+        cached.invalidate();
+        assert_eq!(cached.read_typed::<u32>(BASE + 0xFFE).unwrap(), 0x2222_2222);
+
+        cached.write_typed(BASE + 0x10, 5u32).unwrap();
+        assert_eq!(cached.read_typed::<u32>(BASE + 0x10).unwrap(), 5);
+    }
+
+    #[test]
+    fn cached_pages_expire_after_a_tick() {
+        let mut memory = FakeMemory::with_zeroed_regions(&[(BASE, PAGE_SIZE)]);
+        let mut cached = CachedMemoryReader::new(memory.clone(), Duration::from_millis(1));
+
+        assert_eq!(cached.read_typed::<u32>(BASE).unwrap(), 0);
+        memory.write_typed(BASE, 3u32).unwrap();
+
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(cached.read_typed::<u32>(BASE).unwrap(), 3);
+
+        cached.set_tick(Duration::ZERO);
+        memory.write_typed(BASE, 4u32).unwrap();
+        assert_eq!(cached.read_typed::<u32>(BASE).unwrap(), 4);
+    }
+
+    #[test]
+    fn consistent_snapshots_retry_until_two_captures_agree() {
+        let mut memory = changing_memory(3);
+        let mut snapshot = MemorySnapshot::capture_consistent(&mut memory, &[(BASE, 4)], 5).unwrap();
+
+        assert_eq!(memory.reads, 4);
+        assert_eq!(snapshot.read_typed::<u32>(BASE).unwrap(), 3);
+        assert!(matches!(snapshot.write_typed(BASE, 0u32), Err(WizWalkerError::ReadOnly { .. })));
+        assert!(matches!(snapshot.read_typed::<u32>(BASE + 4), Err(WizWalkerError::NotCaptured { .. })));
+
+        let mut memory = changing_memory(3);
+        let unstable = MemorySnapshot::capture_consistent(&mut memory, &[(BASE, 4)], 3);
+        assert!(matches!(unstable, Err(WizWalkerError::SnapshotUnstable { attempts: 3 })));
+    }
+
+    #[test]
+    fn consistent_snapshots_always_compare_two_captures() {
+        let mut memory = changing_memory(1);
+        MemorySnapshot::capture_consistent(&mut memory, &[(BASE, 4)], 0).unwrap();
+        assert_eq!(memory.reads, 2);
+
+        let mut memory = changing_memory(2);
+        let unstable = MemorySnapshot::capture_consistent(&mut memory, &[(BASE, 4)], 1);
+        assert!(matches!(unstable, Err(WizWalkerError::SnapshotUnstable { attempts: 2 })));
+    }
+}
@@ -0,0 +1,407 @@
+//! Reads any `PropertyClass` instance into a dynamic `serde_json::Value`.
+//!
+//! The object's type name comes from its vtable, and its properties from the
+//! type dump, so debug tools can dump structures no memory object covers yet.
+
+use bytemuck::{pod_read_unaligned, Pod};
+use serde_json::{json, Map, Value};
+
+use crate::error::{Result, WizWalkerError};
+use super::containers::StdVectorLayout;
+use super::memory_reader::WizWalkerMemoryReader;
+use super::type_dump::{ClassInfo, PropertyInfo, TypeDump};
+
+
+/// How many pointers deep `PropertyClassReader` follows by default.
+pub const DEFAULT_MAX_DEPTH: usize = 4;
+
+/// Longest type name read from a vtable.
+const MAX_TYPE_NAME_LENGTH: usize = 60;
+
+/// Largest container read before the data is assumed to be garbage.
+const MAX_REFLECTED_ELEMENTS: usize = 4096;
+
+const STD_STRING_SIZE: usize = 32;
+const SHARED_POINTER_SIZE: usize = 16;
+
+
+/// Reads the type name of the `PropertyClass` at `address`.
+///
+/// The vtable's first function returns the name with a `lea rcx, [rip + name]`
+/// 63 bytes in, sometimes behind a `jmp` thunk.
+pub fn read_type_name<R: WizWalkerMemoryReader>(reader: &mut R, address: usize) -> Result<String> {
+    let vtable: usize = reader.read_typed(address)?;
+    let mut get_class_name: usize = reader.read_typed(vtable)?;
+
+    if reader.read_typed::<u8>(get_class_name)? == 0xE9 {
+        let displacement: i32 = reader.read_typed(get_class_name + 1)?;
+        get_class_name = (get_class_name + 5).wrapping_add_signed(displacement as isize);
+    }
+
+    let lea_instruction = get_class_name + 63;
+    let displacement: i32 = reader.read_typed(lea_instruction + 3)?;
+    let type_name_address = (lea_instruction + 7).wrapping_add_signed(displacement as isize);
+
+    let type_name = reader.read_null_terminated_string(type_name_address, MAX_TYPE_NAME_LENGTH)?;
+    if type_name.is_empty() {
+        return Err(WizWalkerError::InvalidData { address, reason: "vtable has an empty type name".to_string() })
+    }
+
+    Ok(type_name)
+}
+
+
+/// Reads objects property by property, following pointers up to a maximum depth.
+///
+/// Objects become JSON objects with their `$type`, `$address` and every
+/// property by name. Pointers past the maximum depth, or back to an object
+/// being read, become `{"$address": ...}`; properties that fail to read
+/// become `{"$error": ...}` so one bad field does not lose the whole dump.
+pub struct PropertyClassReader<'a> {
+    dump: &'a TypeDump,
+    max_depth: usize,
+}
+
+impl<'a> PropertyClassReader<'a> {
+    pub fn new(dump: &'a TypeDump) -> Self {
+        Self {
+            dump,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// The dumped class of the object at `address`, from its vtable.
+    pub fn type_info<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize) -> Result<&'a ClassInfo> {
+        let type_name = read_type_name(reader, address)?;
+        self.dump.class(&type_name).ok_or(WizWalkerError::TypeNotFound { name: type_name })
+    }
+
+    /// Every property of the object at `address`, ordered by offset.
+    pub fn properties<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize) -> Result<Vec<&'a PropertyInfo>> {
+        let class = self.type_info(reader, address)?;
+        self.dump.all_properties(&class.name)
+    }
+
+    pub fn read_object<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize) -> Result<Value> {
+        let class = self.type_info(reader, address)?;
+        self.read_class(reader, address, class, 0, &mut Vec::new())
+    }
+
+    /// Reads the object at `address` as `type_name`, for objects without a usable vtable.
+    pub fn read_object_as<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize, type_name: &str) -> Result<Value> {
+        let class = self.dump.class(type_name).ok_or(WizWalkerError::TypeNotFound { name: type_name.to_string() })?;
+        self.read_class(reader, address, class, 0, &mut Vec::new())
+    }
+
+    pub fn dump_json<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.read_object(reader, address)?)?)
+    }
+
+    fn read_class<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize, class: &ClassInfo, depth: usize, visited: &mut Vec<usize>) -> Result<Value> {
+        let mut object = Map::new();
+        object.insert("$type".to_string(), json!(class.name));
+        object.insert("$address".to_string(), json!(address));
+
+        visited.push(address);
+
+        for property in self.dump.all_properties(&class.name)? {
+            let value = self.read_property(reader, address + property.offset, property, depth, visited)
+                .unwrap_or_else(|error| json!({ "$error": error.to_string() }));
+
+            object.insert(property.name.clone(), value);
+        }
+
+        visited.pop();
+
+        Ok(Value::Object(object))
+    }
+
+    fn read_property<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize, property: &PropertyInfo, depth: usize, visited: &mut Vec<usize>) -> Result<Value> {
+        let element_addresses = match property.container.as_str() {
+            "Vector" => self.vector_elements(reader, address, property)?,
+            "List" => reader.read_std_list_nodes(address)?,
+            _ => return self.read_value(reader, address, property, depth, visited)
+        };
+
+        element_addresses.into_iter()
+            .map(|element| self.read_value(reader, element, property, depth, visited))
+            .collect::<Result<Vec<Value>>>()
+            .map(Value::Array)
+    }
+
+    fn vector_elements<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize, property: &PropertyInfo) -> Result<Vec<usize>> {
+        let element_size = value_size(property).ok_or_else(|| unsupported(address, property))?;
+        let layout: StdVectorLayout = reader.read_struct(address)?;
+        let count = layout.byte_len() / element_size;
+
+        if count > MAX_REFLECTED_ELEMENTS {
+            return Err(WizWalkerError::InvalidData { address, reason: format!("vector has {count} elements") })
+        }
+
+        Ok((0..count).map(|index| layout.first as usize + index * element_size).collect())
+    }
+
+    fn read_value<R: WizWalkerMemoryReader>(&self, reader: &mut R, address: usize, property: &PropertyInfo, depth: usize, visited: &mut Vec<usize>) -> Result<Value> {
+        let type_name = property.type_name.as_str();
+
+        if is_pointer(property) {
+            let pointee: usize = reader.read_typed(address)?;
+            if pointee == 0 {
+                return Ok(Value::Null)
+            }
+
+            if depth >= self.max_depth || visited.contains(&pointee) {
+                return Ok(json!({ "$address": pointee }))
+            }
+
+            // Pointers are often to derived types, so trust the vtable over the declared type.
+            return match read_type_name(reader, pointee).ok().and_then(|name| self.dump.class(&name)) {
+                Some(class) => self.read_class(reader, pointee, class, depth + 1, visited),
+                None => Ok(json!({ "$address": pointee }))
+            }
+        }
+
+        if type_name.starts_with("enum ") {
+            let value: i32 = reader.read_typed(address)?;
+
+            return Ok(match property.enum_options.iter().find(|(_, option)| **option == value as i64) {
+                Some((name, _)) => json!(name),
+                None => json!(value)
+            })
+        }
+
+        match type_name {
+            "std::string" => return Ok(json!(reader.read_std_string(address)?)),
+            "std::wstring" => return Ok(json!(reader.read_std_wstring(address)?)),
+            _ => {}
+        }
+
+        if let Some(size) = plain_size(type_name) {
+            return Ok(decode_plain(type_name, &reader.read_bytes(address, size)?))
+        }
+
+        match self.dump.class(type_name) {
+            Some(_) if depth >= self.max_depth => Ok(json!({ "$address": address })),
+            Some(class) => self.read_class(reader, address, class, depth + 1, visited),
+            None => Err(unsupported(address, property))
+        }
+    }
+}
+
+
+fn unsupported(address: usize, property: &PropertyInfo) -> WizWalkerError {
+    WizWalkerError::InvalidData { address, reason: format!("unsupported property type \"{}\"", property.type_name) }
+}
+
+fn is_shared_pointer(type_name: &str) -> bool {
+    type_name.starts_with("class SharedPointer<")
+}
+
+fn is_pointer(property: &PropertyInfo) -> bool {
+    property.pointer || property.type_name.ends_with('*') || is_shared_pointer(&property.type_name)
+}
+
+/// Size of one element of `property`, for stepping through vectors.
+fn value_size(property: &PropertyInfo) -> Option<usize> {
+    let type_name = property.type_name.as_str();
+
+    if is_shared_pointer(type_name) {
+        return Some(SHARED_POINTER_SIZE)
+    }
+
+    if is_pointer(property) {
+        return Some(size_of::<usize>())
+    }
+
+    if type_name.starts_with("enum ") {
+        return Some(size_of::<i32>())
+    }
+
+    match type_name {
+        "std::string" | "std::wstring" => Some(STD_STRING_SIZE),
+        _ => plain_size(type_name)
+    }
+}
+
+/// Size of types read straight from their bytes.
+fn plain_size(type_name: &str) -> Option<usize> {
+    let size = match type_name {
+        "bool" | "char" | "signed char" | "unsigned char" | "__int8" | "unsigned __int8" => 1,
+        "short" | "unsigned short" | "wchar_t" | "__int16" | "unsigned __int16" => 2,
+        "int" | "unsigned int" | "long" | "unsigned long" | "__int32" | "unsigned __int32" | "float" | "class Color" => 4,
+        "__int64" | "unsigned __int64" | "long long" | "unsigned long long" | "gid" | "double"
+            | "class Point<int>" | "class Point<float>" | "class Size<int>" => 8,
+        "class Vector3D" => 12,
+        "class Rect<int>" | "class Rect<float>" | "class Quaternion" => 16,
+        _ => return None
+    };
+
+    Some(size)
+}
+
+fn field<T: Pod>(bytes: &[u8], index: usize) -> T {
+    let size = size_of::<T>();
+    pod_read_unaligned(&bytes[index * size..(index + 1) * size])
+}
+
+fn decode_plain(type_name: &str, bytes: &[u8]) -> Value {
+    match type_name {
+        "bool" => json!(bytes[0] != 0),
+        "char" | "signed char" | "__int8" => json!(field::<i8>(bytes, 0)),
+        "unsigned char" | "unsigned __int8" => json!(bytes[0]),
+        "short" | "__int16" => json!(field::<i16>(bytes, 0)),
+        "unsigned short" | "wchar_t" | "unsigned __int16" => json!(field::<u16>(bytes, 0)),
+        "int" | "long" | "__int32" => json!(field::<i32>(bytes, 0)),
+        "unsigned int" | "unsigned long" | "unsigned __int32" => json!(field::<u32>(bytes, 0)),
+        "__int64" | "long long" => json!(field::<i64>(bytes, 0)),
+        "unsigned __int64" | "unsigned long long" | "gid" => json!(field::<u64>(bytes, 0)),
+        "float" => json!(field::<f32>(bytes, 0)),
+        "double" => json!(field::<f64>(bytes, 0)),
+        "class Color" => json!({ "b": bytes[0], "g": bytes[1], "r": bytes[2], "a": bytes[3] }),
+        "class Point<int>" => json!({ "x": field::<i32>(bytes, 0), "y": field::<i32>(bytes, 1) }),
+        "class Point<float>" => json!({ "x": field::<f32>(bytes, 0), "y": field::<f32>(bytes, 1) }),
+        "class Size<int>" => json!({ "width": field::<i32>(bytes, 0), "height": field::<i32>(bytes, 1) }),
+        "class Vector3D" => json!({ "x": field::<f32>(bytes, 0), "y": field::<f32>(bytes, 1), "z": field::<f32>(bytes, 2) }),
+        "class Rect<int>" => json!({
+            "left": field::<i32>(bytes, 0), "top": field::<i32>(bytes, 1),
+            "right": field::<i32>(bytes, 2), "bottom": field::<i32>(bytes, 3),
+        }),
+        "class Rect<float>" => json!({
+            "left": field::<f32>(bytes, 0), "top": field::<f32>(bytes, 1),
+            "right": field::<f32>(bytes, 2), "bottom": field::<f32>(bytes, 3),
+        }),
+        "class Quaternion" => json!({
+            "x": field::<f32>(bytes, 0), "y": field::<f32>(bytes, 1),
+            "z": field::<f32>(bytes, 2), "w": field::<f32>(bytes, 3),
+        }),
+        _ => Value::Null
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::fake_memory::{FakeMemory, FAKE_MODULE_BASE};
+
+    const OBJECT_VTABLE: usize = FAKE_MODULE_BASE;
+    const CHILD_VTABLE: usize = FAKE_MODULE_BASE + 0x10;
+    const OBJECT_GET_NAME: usize = FAKE_MODULE_BASE + 0x100;
+    const CHILD_THUNK: usize = FAKE_MODULE_BASE + 0x200;
+    const CHILD_GET_NAME: usize = FAKE_MODULE_BASE + 0x300;
+    const OBJECT_NAME: usize = FAKE_MODULE_BASE + 0x400;
+    const CHILD_NAME: usize = FAKE_MODULE_BASE + 0x440;
+
+    const OBJECT: usize = FAKE_MODULE_BASE + 0x1000;
+    const CHILD: usize = FAKE_MODULE_BASE + 0x1100;
+    const VALUES: usize = FAKE_MODULE_BASE + 0x1200;
+
+    const DUMP: &str = r#"{ "classes": {
+        "class TestObject": { "hash": 1, "bases": ["class PropertyClass"], "properties": {
+            "m_id": { "type": "unsigned int", "offset": 8, "container": "Static" },
+            "m_name": { "type": "std::string", "offset": 16, "container": "Static" },
+            "m_position": { "type": "class Vector3D", "offset": 48, "container": "Static" },
+            "m_phase": { "type": "enum DuelPhase", "offset": 60, "container": "Static",
+                "enum_options": { "PHASE_STARTING": 0, "PHASE_PLANNING": 2 } },
+            "m_child": { "type": "class TestChild*", "offset": 64, "container": "Static", "pointer": true },
+            "m_values": { "type": "int", "offset": 72, "container": "Vector", "dynamic": true },
+            "m_flags": { "type": "bui4", "offset": 96, "container": "Static" }
+        } },
+        "class TestChild": { "hash": 2, "bases": [], "properties": {
+            "m_scale": { "type": "float", "offset": 8, "container": "Static" },
+            "m_owner": { "type": "class TestObject*", "offset": 16, "container": "Static", "pointer": true }
+        } }
+    } }"#;
+
+    fn write_lea(memory: &mut FakeMemory, function: usize, target: usize) {
+        let lea = function + 63;
+        let displacement = (target as i64 - (lea + 7) as i64) as i32;
+
+        let mut bytes = vec![0x48, 0x8D, 0x0D];
+        bytes.extend_from_slice(&displacement.to_le_bytes());
+        memory.write_bytes(lea, bytes).unwrap();
+    }
+
+    fn object_memory() -> FakeMemory {
+        let mut memory = FakeMemory::with_zeroed_regions(&[(FAKE_MODULE_BASE, 0x2000)]);
+
+        // Type names, one straight from the vtable and one behind a jmp thunk.
+        memory.write_typed(OBJECT_VTABLE, OBJECT_GET_NAME).unwrap();
+        memory.write_typed(CHILD_VTABLE, CHILD_THUNK).unwrap();
+        write_lea(&mut memory, OBJECT_GET_NAME, OBJECT_NAME);
+        write_lea(&mut memory, CHILD_GET_NAME, CHILD_NAME);
+
+        let jump = (CHILD_GET_NAME as i64 - (CHILD_THUNK + 5) as i64) as i32;
+        memory.write_typed(CHILD_THUNK, 0xE9u8).unwrap();
+        memory.write_typed(CHILD_THUNK + 1, jump).unwrap();
+
+        memory.write_bytes(OBJECT_NAME, b"class TestObject\0".to_vec()).unwrap();
+        memory.write_bytes(CHILD_NAME, b"class TestChild\0".to_vec()).unwrap();
+
+        memory.write_typed(OBJECT, OBJECT_VTABLE).unwrap();
+        memory.write_typed(OBJECT + 8, 1234u32).unwrap();
+        memory.write_bytes(OBJECT + 16, b"Gamma".to_vec()).unwrap();
+        memory.write_typed(OBJECT + 32, 5u64).unwrap();
+        memory.write_typed(OBJECT + 40, 15u64).unwrap();
+        memory.write_typed(OBJECT + 48, [1.0f32, 2.0, 3.0]).unwrap();
+        memory.write_typed(OBJECT + 60, 2i32).unwrap();
+        memory.write_typed(OBJECT + 64, CHILD).unwrap();
+        memory.write_typed(OBJECT + 72, [VALUES, VALUES + 12, VALUES + 12]).unwrap();
+        memory.write_typed(VALUES, [7i32, 8, 9]).unwrap();
+
+        memory.write_typed(CHILD, CHILD_VTABLE).unwrap();
+        memory.write_typed(CHILD + 8, 0.5f32).unwrap();
+        memory.write_typed(CHILD + 16, OBJECT).unwrap();
+
+        memory
+    }
+
+    #[test]
+    fn type_names_come_from_the_vtable() {
+        let mut memory = object_memory();
+
+        assert_eq!(read_type_name(&mut memory, OBJECT).unwrap(), "class TestObject");
+        assert_eq!(read_type_name(&mut memory, CHILD).unwrap(), "class TestChild");
+    }
+
+    #[test]
+    fn objects_are_read_into_values() {
+        let mut memory = object_memory();
+        let dump = TypeDump::from_json(DUMP.as_bytes()).unwrap();
+        let reflection = PropertyClassReader::new(&dump);
+
+        let names: Vec<&str> = reflection.properties(&mut memory, OBJECT).unwrap()
+            .into_iter()
+            .map(|property| property.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["m_id", "m_name", "m_position", "m_phase", "m_child", "m_values", "m_flags"]);
+
+        let object = reflection.read_object(&mut memory, OBJECT).unwrap();
+        assert_eq!(object["$type"], "TestObject");
+        assert_eq!(object["m_id"], 1234);
+        assert_eq!(object["m_name"], "Gamma");
+        assert_eq!(object["m_position"], json!({ "x": 1.0, "y": 2.0, "z": 3.0 }));
+        assert_eq!(object["m_phase"], "PHASE_PLANNING");
+        assert_eq!(object["m_values"], json!([7, 8, 9]));
+        assert!(object["m_flags"]["$error"].is_string());
+
+        // The child points back at the object being read.
+        assert_eq!(object["m_child"]["$type"], "TestChild");
+        assert_eq!(object["m_child"]["m_scale"], 0.5);
+        assert_eq!(object["m_child"]["m_owner"], json!({ "$address": OBJECT }));
+    }
+
+    #[test]
+    fn pointers_stop_at_the_maximum_depth() {
+        let mut memory = object_memory();
+        let dump = TypeDump::from_json(DUMP.as_bytes()).unwrap();
+
+        let object = PropertyClassReader::new(&dump).with_max_depth(0).read_object(&mut memory, OBJECT).unwrap();
+        assert_eq!(object["m_child"], json!({ "$address": CHILD }));
+    }
+}
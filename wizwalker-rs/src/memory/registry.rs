@@ -0,0 +1,472 @@
+use crate::error::{Result, WizWalkerError};
+use winapi::um::winnt::HANDLE;
+
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::hooks::{wait_for_export, WizWalkerMemoryHook};
+use super::memory_reader::{MemoryReader, WizWalkerMemoryReader};
+
+
+/// Name and dependencies of a hook type, as seen by `HookRegistry`.
+pub trait HookInfo {
+    const NAME: &'static str;
+
+    /// Hooks that must be active before this one, by name.
+    const DEPENDENCIES: &'static [&'static str] = &[];
+}
+
+
+/// The object safe face of a hook, so hooks of different types can share a registry.
+///
+/// Implemented for every `WizWalkerMemoryHook` that also implements `HookInfo`.
+pub trait ActivatableHook: Any {
+    fn name(&self) -> &'static str;
+    fn dependencies(&self) -> &'static [&'static str];
+    fn activate(&mut self) -> Result<()>;
+    fn deactivate(&mut self) -> Result<()>;
+
+    /// The process, address and original bytes of the jump site while hooked.
+    fn patched_site(&mut self) -> Option<(HANDLE, usize, Vec<u8>)>;
+
+    /// Waits for the hook's export to become non-null; see `wait_for_export`.
+    fn wait_for_export(&mut self, interval: Duration, timeout: Duration) -> Result<usize>;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<H: WizWalkerMemoryHook + HookInfo + Any> ActivatableHook for H {
+    fn name(&self) -> &'static str {
+        H::NAME
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        H::DEPENDENCIES
+    }
+
+    fn activate(&mut self) -> Result<()> {
+        self.hook()
+    }
+
+    fn deactivate(&mut self) -> Result<()> {
+        self.unhook()
+    }
+
+    fn patched_site(&mut self) -> Option<(HANDLE, usize, Vec<u8>)> {
+        let original_bytes = self.jump_original_bytecode().clone();
+
+        match original_bytes.is_empty() {
+            true => None,
+            false => Some((self.process(), *self.jump_address(), original_bytes))
+        }
+    }
+
+    fn wait_for_export(&mut self, interval: Duration, timeout: Duration) -> Result<usize> {
+        let export_address = self.export_address()
+            .ok_or(WizWalkerError::HookNotActive { name: H::NAME.to_string() })?;
+
+        wait_for_export(self, export_address, interval, timeout)
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+
+/// Original bytes to put back if the process dies before they are restored normally.
+struct EmergencyRestore {
+    // Stored as an integer so the list can live in a static.
+    process: usize,
+    address: usize,
+    original: Vec<u8>,
+}
+
+static EMERGENCY_RESTORES: Mutex<Vec<EmergencyRestore>> = Mutex::new(Vec::new());
+
+/// Bumped whenever `emergency_restore_all` restores anything, so registries
+/// can tell their hooks were taken out from under them.
+static EMERGENCY_RESTORE_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+
+/// Remembers that `original` must be written back at `address` if we crash.
+pub fn register_emergency_restore(process: HANDLE, address: usize, original: Vec<u8>) {
+    let mut restores = EMERGENCY_RESTORES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    restores.push(EmergencyRestore { process: process as usize, address, original });
+}
+
+
+/// Forgets the emergency restore for `address`, once it was restored normally.
+pub fn unregister_emergency_restore(process: HANDLE, address: usize) {
+    let mut restores = EMERGENCY_RESTORES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    restores.retain(|restore| restore.process != process as usize || restore.address != address);
+}
+
+
+/// The original bytes registered for `address`, if any.
+#[cfg(test)]
+pub(crate) fn emergency_restore_for(process: HANDLE, address: usize) -> Option<Vec<u8>> {
+    let restores = EMERGENCY_RESTORES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    restores.iter()
+        .find(|restore| restore.process == process as usize && restore.address == address)
+        .map(|restore| restore.original.clone())
+}
+
+
+/// Forgets every emergency restore for `process`, e.g. once it exited.
+pub fn unregister_process_emergency_restores(process: HANDLE) {
+    let mut restores = EMERGENCY_RESTORES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    restores.retain(|restore| restore.process != process as usize);
+}
+
+
+/// Writes back every registered original, newest first, and returns how many failed.
+///
+/// Used from the Ctrl-C and panic handlers, so it never panics and only logs failures.
+pub fn emergency_restore_all() -> usize {
+    let restores = {
+        let mut restores = EMERGENCY_RESTORES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::take(&mut *restores)
+    };
+
+    if !restores.is_empty() {
+        EMERGENCY_RESTORE_GENERATION.fetch_add(1, Ordering::SeqCst);
+    }
+
+    let mut failed = 0;
+    for restore in restores.into_iter().rev() {
+        let mut memory = MemoryReader::new(restore.process as HANDLE);
+
+        if let Err(error) = memory.write_bytes_guarded(restore.address, restore.original) {
+            log::error!("Emergency restore of \"{:#x}\" failed: {error}", restore.address);
+            failed += 1;
+        }
+    }
+
+    failed
+}
+
+
+/// Restores every patch on Ctrl-C and on panics of the main thread.
+///
+/// `HookRegistry` already cleans up when dropped, which covers normal exits
+/// and unwinding; these handlers cover the cases where destructors never run.
+/// Panics on other threads only end that thread, or are caught as with
+/// `spawn_blocking`, so they leave the hooks alone. Registries notice an
+/// emergency restore and mark their hooks inactive. The previous panic hook
+/// still runs afterwards. Call once, early in `main`.
+pub fn install_cleanup_handlers() -> Result<()> {
+    ctrlc::set_handler(|| {
+        emergency_restore_all();
+        std::process::exit(130);
+    })?;
+
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() == Some("main") {
+            emergency_restore_all();
+        }
+
+        previous_hook(info);
+    }));
+
+    Ok(())
+}
+
+
+/// Activation state of a registered hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookStatus {
+    pub name: &'static str,
+    pub active: bool,
+    pub dependencies: &'static [&'static str],
+}
+
+
+/// Owns a client's hooks and installs them in dependency order.
+///
+/// Activating a hook first activates what it depends on; deactivating one
+/// first deactivates the active hooks depending on it. Active hooks are
+/// registered for emergency restore, and dropping the registry deactivates
+/// them all, newest first. After an emergency restore every hook counts as
+/// inactive, since its site already holds the original bytes again.
+#[derive(Default)]
+pub struct HookRegistry {
+    hooks: Vec<Box<dyn ActivatableHook>>,
+    active: Vec<&'static str>,
+    restore_generation: usize,
+}
+
+impl HookRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, hook: Box<dyn ActivatableHook>) -> Result<()> {
+        if self.index_of(hook.name()).is_some() {
+            return Err(WizWalkerError::HookAlreadyRegistered { name: hook.name().to_string() })
+        }
+
+        self.hooks.push(hook);
+        Ok(())
+    }
+
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.index_of(name).is_some()
+    }
+
+    pub fn is_active(&self, name: &str) -> bool {
+        !self.emergency_restored() && self.active.contains(&name)
+    }
+
+    /// Names of the active hooks, in activation order.
+    pub fn active_hooks(&self) -> &[&'static str] {
+        match self.emergency_restored() {
+            true => &[],
+            false => &self.active
+        }
+    }
+
+    pub fn status(&self) -> Vec<HookStatus> {
+        self.hooks.iter()
+            .map(|hook| HookStatus {
+                name: hook.name(),
+                active: self.is_active(hook.name()),
+                dependencies: hook.dependencies(),
+            })
+            .collect()
+    }
+
+    /// The registered hook named `name`, if it is an `H`.
+    pub fn get<H: ActivatableHook>(&mut self, name: &str) -> Option<&mut H> {
+        let index = self.index_of(name)?;
+        self.hooks[index].as_any_mut().downcast_mut::<H>()
+    }
+
+    /// Waits until the export of the hook named `name` is filled in by the game.
+    pub fn wait_for_hook_value(&mut self, name: &str, interval: Duration, timeout: Duration) -> Result<usize> {
+        let index = self.index_of(name).ok_or(WizWalkerError::HookNotRegistered { name: name.to_string() })?;
+        self.hooks[index].wait_for_export(interval, timeout)
+    }
+
+    pub fn activate(&mut self, name: &str) -> Result<()> {
+        self.sync_emergency_restores();
+        self.activate_inner(name, &mut Vec::new())
+    }
+
+    pub fn deactivate(&mut self, name: &str) -> Result<()> {
+        self.sync_emergency_restores();
+
+        let index = self.index_of(name).ok_or(WizWalkerError::HookNotRegistered { name: name.to_string() })?;
+        let name = self.hooks[index].name();
+
+        if !self.is_active(name) {
+            return Ok(())
+        }
+
+        let dependents: Vec<&'static str> = self.active.iter()
+            .copied()
+            .filter(|active_name| {
+                self.index_of(active_name)
+                    .is_some_and(|active_index| self.hooks[active_index].dependencies().contains(&name))
+            })
+            .collect();
+
+        for dependent in dependents {
+            self.deactivate(dependent)?;
+        }
+
+        let hook = &mut self.hooks[index];
+        let site = hook.patched_site();
+        hook.deactivate()?;
+
+        if let Some((process, address, _)) = site {
+            unregister_emergency_restore(process, address);
+        }
+
+        self.active.retain(|active_name| *active_name != name);
+        Ok(())
+    }
+
+    /// Deactivates every active hook, newest first, continuing past failures.
+    pub fn deactivate_all(&mut self) -> Result<()> {
+        self.sync_emergency_restores();
+
+        let mut errors: Vec<String> = Vec::new();
+
+        while let Some(name) = self.active.last().copied() {
+            if let Err(error) = self.deactivate(name) {
+                errors.push(format!("{name}: {error}"));
+                self.active.retain(|active_name| *active_name != name);
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(WizWalkerError::RollbackFailed { count: errors.len(), details: errors.join("; ") })
+        }
+
+        Ok(())
+    }
+
+    /// Marks every hook inactive without touching memory, for when the process is already gone.
+    pub fn forget_active(&mut self) {
+        self.active.clear();
+    }
+
+    /// Whether an emergency restore ran since the registry last looked.
+    fn emergency_restored(&self) -> bool {
+        EMERGENCY_RESTORE_GENERATION.load(Ordering::SeqCst) != self.restore_generation
+    }
+
+    /// Forgets hooks an emergency restore already took out, so they are not unhooked twice.
+    fn sync_emergency_restores(&mut self) {
+        if !self.emergency_restored() {
+            return
+        }
+
+        if !self.active.is_empty() {
+            log::warn!("Hooks {:?} were removed by an emergency restore", self.active);
+        }
+
+        self.forget_active();
+        self.restore_generation = EMERGENCY_RESTORE_GENERATION.load(Ordering::SeqCst);
+    }
+
+    fn index_of(&self, name: &str) -> Option<usize> {
+        self.hooks.iter().position(|hook| hook.name() == name)
+    }
+
+    fn activate_inner(&mut self, name: &str, activating: &mut Vec<&'static str>) -> Result<()> {
+        let index = self.index_of(name).ok_or(WizWalkerError::HookNotRegistered { name: name.to_string() })?;
+        let name = self.hooks[index].name();
+
+        if self.is_active(name) {
+            return Ok(())
+        }
+
+        if activating.contains(&name) {
+            return Err(WizWalkerError::HookDependencyCycle { name: name.to_string() })
+        }
+
+        activating.push(name);
+        for dependency in self.hooks[index].dependencies() {
+            self.activate_inner(dependency, activating)?;
+        }
+        activating.pop();
+
+        let hook = &mut self.hooks[index];
+        if let Err(error) = hook.activate() {
+            // The hook may be partially installed; take out whatever made it in.
+            if let Err(cleanup_error) = hook.deactivate() {
+                log::error!("Failed to clean up after hook \"{name}\" failed to activate: {cleanup_error}");
+            }
+
+            return Err(error)
+        }
+
+        if let Some((process, address, original)) = hook.patched_site() {
+            register_emergency_restore(process, address, original);
+        }
+
+        self.active.push(name);
+        Ok(())
+    }
+}
+
+impl Drop for HookRegistry {
+    fn drop(&mut self) {
+        if let Err(error) = self.deactivate_all() {
+            log::error!("Hook registry cleanup on drop failed: {error}");
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    struct StubHook {
+        name: &'static str,
+        dependencies: &'static [&'static str],
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ActivatableHook for StubHook {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn dependencies(&self) -> &'static [&'static str] {
+            self.dependencies
+        }
+
+        fn activate(&mut self) -> Result<()> {
+            self.log.lock().unwrap().push(format!("+{}", self.name));
+            Ok(())
+        }
+
+        fn deactivate(&mut self) -> Result<()> {
+            self.log.lock().unwrap().push(format!("-{}", self.name));
+            Ok(())
+        }
+
+        fn patched_site(&mut self) -> Option<(HANDLE, usize, Vec<u8>)> {
+            None
+        }
+
+        fn wait_for_export(&mut self, _interval: Duration, _timeout: Duration) -> Result<usize> {
+            Err(WizWalkerError::HookNotActive { name: self.name.to_string() })
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    fn registry(hooks: &[(&'static str, &'static [&'static str])]) -> (HookRegistry, Arc<Mutex<Vec<String>>>) {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = HookRegistry::new();
+
+        for (name, dependencies) in hooks {
+            registry.register(Box::new(StubHook { name, dependencies, log: log.clone() })).unwrap();
+        }
+
+        (registry, log)
+    }
+
+    #[test]
+    fn dependencies_activate_first_and_deactivate_last() {
+        let (mut registry, log) = registry(&[("Player", &[]), ("Teleport", &["Player"])]);
+
+        registry.activate("Teleport").unwrap();
+        assert_eq!(registry.active_hooks(), ["Player", "Teleport"]);
+
+        registry.deactivate("Player").unwrap();
+        assert!(registry.active_hooks().is_empty());
+        assert_eq!(*log.lock().unwrap(), ["+Player", "+Teleport", "-Teleport", "-Player"]);
+    }
+
+    #[test]
+    fn dependency_cycles_and_unknown_hooks_are_rejected() {
+        let (mut registry, _) = registry(&[("A", &["B"]), ("B", &["A"]), ("C", &["Missing"])]);
+
+        assert!(matches!(registry.activate("A"), Err(WizWalkerError::HookDependencyCycle { .. })));
+        assert!(matches!(registry.activate("C"), Err(WizWalkerError::HookNotRegistered { .. })));
+        assert!(registry.active_hooks().is_empty());
+    }
+
+    #[test]
+    fn drop_deactivates_everything() {
+        let (mut registry, log) = registry(&[("Player", &[]), ("Duel", &[])]);
+
+        registry.activate("Player").unwrap();
+        registry.activate("Duel").unwrap();
+        drop(registry);
+
+        assert_eq!(*log.lock().unwrap(), ["+Player", "+Duel", "-Duel", "-Player"]);
+    }
+}
@@ -0,0 +1,79 @@
+//! Every byte pattern wizwalker-rs scans the client for, in one place so a
+//! game update only needs fixing here.
+//!
+//! Patterns are `regex::bytes` expressions; `(?s-u)` makes `.` match any byte
+//! and `\xNN` match raw bytes instead of unicode code points.
+
+
+pub const CLIENT_MODULE: &str = "WizardGraphicalClient.exe";
+
+
+/// A pattern and the module it is scanned in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub pattern: &'static str,
+    pub module: &'static str,
+}
+
+impl Signature {
+    /// The `(pattern, module)` pair returned by `WizWalkerMemoryHook::get_pattern`.
+    pub fn to_owned_pair(&self) -> (String, String) {
+        (self.pattern.to_string(), self.module.to_string())
+    }
+}
+
+
+/// `movsd xmm0, [rax+0x58]` reading a position, with `rax` pointing at an actor body.
+pub const PLAYER_HOOK: Signature = Signature {
+    pattern: r"(?s-u)\xF2\x0F\x10\x40\x58\xF2",
+    module: CLIENT_MODULE,
+};
+
+
+/// `mov rcx, [rax+0x1B8]` loading the game client's current client object; `rax` is the game client.
+pub const CLIENT_HOOK: Signature = Signature {
+    pattern: r"(?s-u)\x48\x8B\x88\xB8\x01\x00\x00\x48\x85\xC9\x74.\x48\x8B\x01\xFF\x90",
+    module: CLIENT_MODULE,
+};
+
+
+/// Entry of the per-frame duel update; `rcx` is the `Duel` being updated.
+pub const DUEL_HOOK: Signature = Signature {
+    pattern: r"(?s-u)\x48\x89\x5C\x24\x10\x48\x89\x74\x24\x18\x55\x57\x41\x54\x41\x56\x41\x57\x48\x8D\x6C\x24.\x48\x81\xEC....\x48\x8B\x05....\x48\x33\xC4\x48\x89\x45.\x4C\x8B\xF9",
+    module: CLIENT_MODULE,
+};
+
+
+/// `movss xmm0, [r14+0xCFC]` reading the active quest's position; `r14` is the quest client manager.
+pub const QUEST_HOOK: Signature = Signature {
+    pattern: r"(?s-u)\xF3\x41\x0F\x10\x86\xFC\x0C\x00\x00\xF3\x41\x0F\x10\x8E\x00\x0D\x00\x00",
+    module: CLIENT_MODULE,
+};
+
+
+/// Entry of the actor body movement update; `rcx` is the body being moved.
+pub const MOVEMENT_TELEPORT_HOOK: Signature = Signature {
+    pattern: r"(?s-u)\x48\x89\x5C\x24\x08\x57\x48\x83\xEC.\x48\x8B\x99....\x48\x8B\xF9\x48\x85\xDB\x74.\x8B\x83",
+    module: CLIENT_MODULE,
+};
+
+
+/// Inside the GUI frame update, right after the root window is loaded into `rbx`.
+pub const ROOT_WINDOW_HOOK: Signature = Signature {
+    pattern: r"(?s-u)\x48\x8B\x01\x48\x8B\xCB\xFF\x50\x70\x84\xC0",
+    module: CLIENT_MODULE,
+};
+
+
+/// `movss xmm5, [r8]` in the UI draw path; `r15` points at the render context pointer.
+pub const RENDER_CONTEXT_HOOK: Signature = Signature {
+    pattern: r"(?s-u)\xF3\x41\x0F\x10\x28\xF3\x0F\x10\x56\x04\x48\x63\xC1",
+    module: CLIENT_MODULE,
+};
+
+
+/// Start of the unused autobot function, reused as a code cave by `HookHandler`.
+pub const AUTOBOT: Signature = Signature {
+    pattern: r"(?s-u)\x48\x8B\xC4\x55\x41\x54\x41\x55\x41\x56\x41\x57\x48...\x48...\x48\x89\x58\x10\x48\x89\x70\x18\x48\x89\x78\x20\x48\x33\xC4...\x4C\x8B\xE9.......\x80...\x0F",
+    module: CLIENT_MODULE,
+};
@@ -0,0 +1,391 @@
+//! Field offsets read from a dump of the client's `PropertyClass` type system.
+//!
+//! Dumps are the JSON written by type dumpers such as wiztype. Installing one
+//! with `install_type_dump` makes memory objects look their fields up by name,
+//! so a game patch only needs a fresh dump instead of new hardcoded offsets.
+
+use serde_json::Value;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+use crate::error::{Result, WizWalkerError};
+
+
+/// Deepest base class chain followed when looking up inherited properties.
+const MAX_BASE_DEPTH: usize = 32;
+
+
+/// KingsIsle's string id hash, used for property names, enum values and school names.
+pub fn string_id(name: &str) -> u32 {
+    let mut result: i32 = 0;
+    let mut shift: i32 = 0;
+
+    for byte in name.bytes() {
+        let value = byte as i32 - 32;
+        result ^= value.wrapping_shl(shift as u32);
+
+        if shift > 24 {
+            result ^= value >> (32 - shift);
+            if shift >= 27 {
+                shift -= 32;
+            }
+        }
+
+        shift += 5;
+    }
+
+    result.unsigned_abs()
+}
+
+
+/// A property of a dumped class.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyInfo {
+    pub name: String,
+    pub type_name: String,
+    pub offset: usize,
+    pub hash: u32,
+    pub flags: u32,
+    pub container: String,
+    pub dynamic: bool,
+    pub pointer: bool,
+    /// Named values of enum properties.
+    pub enum_options: BTreeMap<String, i64>,
+}
+
+
+/// A dumped `PropertyClass` type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassInfo {
+    /// Name without the `class `/`struct ` prefix the dump uses.
+    pub name: String,
+    pub hash: u32,
+    pub bases: Vec<String>,
+    pub properties: HashMap<String, PropertyInfo>,
+}
+
+
+/// Every class of a type dump, indexed by name and type hash.
+#[derive(Debug, Clone, Default)]
+pub struct TypeDump {
+    classes: HashMap<String, ClassInfo>,
+    names_by_hash: HashMap<u32, String>,
+}
+
+impl TypeDump {
+    pub fn from_json(data: &[u8]) -> Result<Self> {
+        let root: Value = serde_json::from_slice(data)?;
+        let classes = root.get("classes")
+            .and_then(Value::as_object)
+            .ok_or(parse_error("missing \"classes\" object"))?;
+
+        let mut dump = TypeDump::default();
+
+        for (class_name, class) in classes {
+            let class_info = parse_class(class_name, class)?;
+
+            dump.names_by_hash.insert(class_info.hash, class_info.name.clone());
+            dump.classes.insert(class_info.name.clone(), class_info);
+        }
+
+        Ok(dump)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_json(&std::fs::read(path)?)
+    }
+
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// The class named `name`, with or without its `class ` prefix.
+    pub fn class(&self, name: &str) -> Option<&ClassInfo> {
+        self.classes.get(strip_class_prefix(name))
+    }
+
+    pub fn class_by_hash(&self, hash: u32) -> Option<&ClassInfo> {
+        self.classes.get(self.names_by_hash.get(&hash)?)
+    }
+
+    /// Property `property` of `class`, including ones inherited from its bases.
+    pub fn property(&self, class: &str, property: &str) -> Result<&PropertyInfo> {
+        let mut current = vec![self.class(class).ok_or(WizWalkerError::TypeNotFound { name: class.to_string() })?];
+
+        for _ in 0..MAX_BASE_DEPTH {
+            if let Some(found) = current.iter().find_map(|class_info| class_info.properties.get(property)) {
+                return Ok(found)
+            }
+
+            current = current.iter()
+                .flat_map(|class_info| class_info.bases.iter())
+                .filter_map(|base| self.class(base))
+                .collect();
+
+            if current.is_empty() {
+                break
+            }
+        }
+
+        Err(WizWalkerError::PropertyNotFound { class: class.to_string(), property: property.to_string() })
+    }
+
+    /// Every property of `class` and its bases, ordered by offset.
+    pub fn all_properties(&self, class: &str) -> Result<Vec<&PropertyInfo>> {
+        let mut pending = vec![self.class(class).ok_or(WizWalkerError::TypeNotFound { name: class.to_string() })?];
+        let mut visited: Vec<&str> = Vec::new();
+        let mut properties: Vec<&PropertyInfo> = Vec::new();
+
+        while let Some(class_info) = pending.pop() {
+            if visited.contains(&class_info.name.as_str()) || visited.len() >= MAX_BASE_DEPTH {
+                continue
+            }
+            visited.push(&class_info.name);
+
+            for property in class_info.properties.values() {
+                if !properties.iter().any(|known| known.name == property.name) {
+                    properties.push(property);
+                }
+            }
+
+            pending.extend(class_info.bases.iter().filter_map(|base| self.class(base)));
+        }
+
+        properties.sort_by_key(|property| property.offset);
+        Ok(properties)
+    }
+
+    /// Offset of a `Type::property` path, e.g. `"ClientObject::m_location"`.
+    pub fn offset(&self, path: &str) -> Result<usize> {
+        let (class, property) = path.rsplit_once("::")
+            .ok_or(WizWalkerError::PropertyNotFound { class: String::new(), property: path.to_string() })?;
+
+        Ok(self.property(class, property)?.offset)
+    }
+}
+
+
+fn parse_error(reason: &str) -> WizWalkerError {
+    WizWalkerError::ParseFailed { file: "type dump".to_string(), reason: reason.to_string() }
+}
+
+fn strip_class_prefix(name: &str) -> &str {
+    name.strip_prefix("class ")
+        .or_else(|| name.strip_prefix("struct "))
+        .unwrap_or(name)
+}
+
+fn parse_class(class_name: &str, class: &Value) -> Result<ClassInfo> {
+    let hash = class.get("hash")
+        .and_then(Value::as_u64)
+        .ok_or(parse_error(&format!("class \"{class_name}\" has no hash")))?;
+
+    let bases = class.get("bases")
+        .and_then(Value::as_array)
+        .map(|bases| bases.iter()
+            .filter_map(Value::as_str)
+            .map(|base| strip_class_prefix(base).to_string())
+            .collect())
+        .unwrap_or_default();
+
+    let mut properties = HashMap::new();
+    if let Some(dumped_properties) = class.get("properties").and_then(Value::as_object) {
+        for (property_name, property) in dumped_properties {
+            properties.insert(property_name.clone(), parse_property(class_name, property_name, property)?);
+        }
+    }
+
+    Ok(ClassInfo {
+        name: strip_class_prefix(class_name).to_string(),
+        hash: u32::try_from(hash)?,
+        bases,
+        properties,
+    })
+}
+
+fn parse_property(class_name: &str, property_name: &str, property: &Value) -> Result<PropertyInfo> {
+    let offset = property.get("offset")
+        .and_then(Value::as_u64)
+        .ok_or(parse_error(&format!("property \"{class_name}::{property_name}\" has no offset")))?;
+
+    let as_u32 = |key: &str| property.get(key).and_then(Value::as_u64).unwrap_or(0) as u32;
+    let as_bool = |key: &str| property.get(key).and_then(Value::as_bool).unwrap_or(false);
+
+    let enum_options = property.get("enum_options")
+        .and_then(Value::as_object)
+        .map(|options| options.iter()
+            .filter_map(|(name, value)| Some((name.clone(), value.as_i64()?)))
+            .collect())
+        .unwrap_or_default();
+
+    Ok(PropertyInfo {
+        name: property_name.to_string(),
+        type_name: property.get("type").and_then(Value::as_str).unwrap_or_default().to_string(),
+        offset: usize::try_from(offset)?,
+        hash: as_u32("hash"),
+        flags: as_u32("flags"),
+        container: property.get("container").and_then(Value::as_str).unwrap_or_default().to_string(),
+        dynamic: as_bool("dynamic"),
+        pointer: as_bool("pointer"),
+        enum_options,
+    })
+}
+
+
+static INSTALLED_TYPE_DUMP: RwLock<Option<Arc<TypeDump>>> = RwLock::new(None);
+
+/// Properties already logged as missing from the dump, so each is logged once.
+static LOGGED_FALLBACKS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+
+
+/// Makes memory objects read their fields at the offsets in `dump`.
+pub fn install_type_dump(dump: TypeDump) {
+    let mut installed = INSTALLED_TYPE_DUMP.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    *installed = Some(Arc::new(dump));
+}
+
+/// Goes back to the built-in offsets.
+pub fn uninstall_type_dump() {
+    let mut installed = INSTALLED_TYPE_DUMP.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    *installed = None;
+}
+
+pub fn installed_type_dump() -> Option<Arc<TypeDump>> {
+    INSTALLED_TYPE_DUMP.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// Offset of `class::property` in the installed dump, failing without one.
+///
+/// For fields that have no built-in offset to fall back on.
+pub fn dumped_offset(class: &str, property: &str) -> Result<usize> {
+    match installed_type_dump() {
+        Some(dump) => Ok(dump.property(class, property)?.offset),
+        None => Err(WizWalkerError::PropertyNotFound { class: class.to_string(), property: property.to_string() })
+    }
+}
+
+/// Offset of `class::property` in the installed dump, or `fallback` without one.
+///
+/// Also falls back when the dump lacks the property, so objects keep working
+/// with partial dumps.
+pub fn resolve_offset(class: &str, property: &str, fallback: usize) -> usize {
+    let Some(dump) = installed_type_dump() else {
+        return fallback
+    };
+
+    match dump.property(class, property) {
+        Ok(property_info) => property_info.offset,
+        Err(error) => {
+            let mut logged = LOGGED_FALLBACKS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if logged.insert(format!("{class}::{property}")) {
+                log::debug!("Using built-in offset {fallback} for {class}::{property}: {error}");
+            }
+
+            fallback
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::memory_objects::enums::MagicSchool;
+
+    const DUMP: &str = r#"{
+        "version": 2,
+        "classes": {
+            "class CoreObject": {
+                "bases": ["class PropertyClass"],
+                "hash": 1152306685,
+                "properties": {
+                    "m_location": { "type": "class Vector3D", "id": 0, "offset": 176, "flags": 31, "container": "Static", "dynamic": false, "pointer": false, "hash": 2239683611 },
+                    "m_fScale": { "type": "float", "id": 1, "offset": 204, "flags": 31, "container": "Static", "dynamic": false, "pointer": false, "hash": 503137701 }
+                }
+            },
+            "class ClientObject": {
+                "bases": ["class CoreObject"],
+                "hash": 350837933,
+                "properties": {
+                    "m_characterId": { "type": "gid", "id": 0, "offset": 448, "flags": 31, "container": "Static", "dynamic": false, "pointer": false, "hash": 210498418 }
+                }
+            },
+            "class Duel": {
+                "bases": ["class PropertyClass"],
+                "hash": 1497436519,
+                "properties": {
+                    "m_duelPhase": { "type": "enum DuelPhase", "id": 0, "offset": 200, "flags": 2097159, "container": "Static", "dynamic": false, "pointer": false, "hash": 1812331563,
+                        "enum_options": { "PHASE_STARTING": 0, "PHASE_PLANNING": 2, "__DEFAULT": "PHASE_STARTING" } }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn string_id_matches_the_magic_school_ids() {
+        assert_eq!(string_id("Ice"), MagicSchool::Ice as u32);
+        assert_eq!(string_id("Fire"), MagicSchool::Fire as u32);
+        assert_eq!(string_id("Storm"), MagicSchool::Storm as u32);
+        assert_eq!(string_id("Balance"), MagicSchool::Balance as u32);
+        assert_eq!(string_id("Shadow"), MagicSchool::Shadow as u32);
+    }
+
+    #[test]
+    fn properties_resolve_through_base_classes() {
+        let dump = TypeDump::from_json(DUMP.as_bytes()).unwrap();
+
+        assert_eq!(dump.len(), 3);
+        assert_eq!(dump.offset("ClientObject::m_characterId").unwrap(), 448);
+        assert_eq!(dump.offset("ClientObject::m_location").unwrap(), 176);
+        assert_eq!(dump.offset("class CoreObject::m_fScale").unwrap(), 204);
+        assert_eq!(dump.class_by_hash(350837933).unwrap().name, "ClientObject");
+
+        let phase = dump.property("Duel", "m_duelPhase").unwrap();
+        assert_eq!(phase.enum_options.get("PHASE_PLANNING"), Some(&2));
+        assert_eq!(phase.enum_options.len(), 2);
+
+        let names: Vec<&str> = dump.all_properties("ClientObject").unwrap()
+            .into_iter()
+            .map(|property| property.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["m_location", "m_fScale", "m_characterId"]);
+
+        assert!(matches!(dump.offset("ClientObject::m_missing"), Err(WizWalkerError::PropertyNotFound { .. })));
+        assert!(matches!(dump.offset("Missing::m_location"), Err(WizWalkerError::TypeNotFound { .. })));
+    }
+
+    #[test]
+    fn malformed_dumps_are_rejected() {
+        assert!(TypeDump::from_json(br#"{ "version": 2 }"#).is_err());
+        assert!(TypeDump::from_json(br#"{ "classes": { "class A": { "properties": {} } } }"#).is_err());
+    }
+
+    /// Uninstalls the process wide dump when the test ends, even if it panics.
+    struct InstalledDumpGuard;
+
+    impl Drop for InstalledDumpGuard {
+        fn drop(&mut self) {
+            uninstall_type_dump();
+        }
+    }
+
+    #[test]
+    fn offsets_fall_back_without_the_property() {
+        // Installed dumps are process wide, so use a class no other test reads.
+        let dump = TypeDump::from_json(br#"{ "classes": { "class ResolveTestObject": {
+            "hash": 2, "properties": { "m_moved": { "offset": 24 } }
+        } } }"#).unwrap();
+        install_type_dump(dump);
+        let _guard = InstalledDumpGuard;
+
+        assert_eq!(resolve_offset("ResolveTestObject", "m_moved", 16), 24);
+        assert_eq!(resolve_offset("ResolveTestObject", "m_missing", 32), 32);
+        assert_eq!(resolve_offset("MissingObject", "m_moved", 40), 40);
+    }
+}
@@ -5,13 +5,17 @@ use std::path::{Path, PathBuf};
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
 use std::fmt::Debug;
+use std::ptr::null_mut;
 
 use byteorder::{LittleEndian, ReadBytesExt};
 use winapi::um::processthreadsapi::GetExitCodeProcess;
 use winapi::um::sysinfoapi::GetSystemDirectoryW;
 use winapi::um::winnt::HANDLE;
-use winapi::shared::minwindef::DWORD;
-use anyhow::{Result, anyhow};
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE};
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{EnumWindows, GetWindowThreadProcessId, IsWindowVisible};
+
+use crate::error::{Result, WizWalkerError};
 
 use flate2::read::ZlibDecoder;
 
@@ -48,7 +52,7 @@ pub fn check_if_process_running(handle: HANDLE) -> Result<bool> {
     match exit_code {
         259 => Ok(true),
         0 => Ok(false),
-        _ => Err(anyhow!("Unknown process exit code: {exit_code}")),
+        _ => Err(WizWalkerError::UnknownExitCode(exit_code)),
     }
 }
 
@@ -67,10 +71,159 @@ pub fn get_system_directory(max_size: usize) -> String {
 }
 
 
+/// A position in the game world.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Default)]
+pub struct XYZ {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32
+}
+
+// Three `f32`s with `repr(C)`: no padding, and every bit pattern is valid.
+unsafe impl bytemuck::Zeroable for XYZ {}
+unsafe impl bytemuck::Pod for XYZ {}
+
+impl XYZ {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn distance(&self, other: &XYZ) -> f32 {
+        (*self - *other).length()
+    }
+
+    /// Distance ignoring height, which is what the game uses for most range checks.
+    pub fn distance_2d(&self, other: &XYZ) -> f32 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+
+    pub fn length(&self) -> f32 {
+        (self.x * self.x + self.y * self.y + self.z * self.z).sqrt()
+    }
+}
+
+impl std::ops::Add for XYZ {
+    type Output = XYZ;
+
+    fn add(self, other: XYZ) -> XYZ {
+        XYZ::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl std::ops::Sub for XYZ {
+    type Output = XYZ;
+
+    fn sub(self, other: XYZ) -> XYZ {
+        XYZ::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Debug for XYZ {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<XYZ (x: {}, y: {}, z: {})>",
+            &self.x, &self.y, &self.z)
+    }
+}
+
+
+/// A screen-space rectangle in pixels, as stored by UI windows.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rectangle {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32
+}
+
+// Four `i32`s with `repr(C)`: no padding, and every bit pattern is valid.
+unsafe impl bytemuck::Zeroable for Rectangle {}
+unsafe impl bytemuck::Pod for Rectangle {}
+
+impl Rectangle {
+    pub fn new(x1: i32, y1: i32, x2: i32, y2: i32) -> Self {
+        Self { x1, y1, x2, y2 }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.x2 - self.x1
+    }
+
+    pub fn height(&self) -> i32 {
+        self.y2 - self.y1
+    }
+
+    pub fn center(&self) -> (i32, i32) {
+        ((self.x1 + self.x2) / 2, (self.y1 + self.y2) / 2)
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.x1 <= x && x < self.x2 && self.y1 <= y && y < self.y2
+    }
+
+    /// Converts a window rectangle to client coordinates.
+    ///
+    /// Window rectangles are relative to their parent and unscaled; `parents`
+    /// goes from the direct parent up to the root, and `factor` is the render
+    /// context's UI scale.
+    pub fn scale_to_client(&self, parents: &[Rectangle], factor: f32) -> Rectangle {
+        let (offset_x, offset_y) = parents.iter()
+            .fold((0, 0), |(x, y), parent| (x + parent.x1, y + parent.y1));
+
+        let scale = |value: i32| (value as f32 * factor) as i32;
+
+        Rectangle::new(
+            scale(self.x1 + offset_x),
+            scale(self.y1 + offset_y),
+            scale(self.x2 + offset_x),
+            scale(self.y2 + offset_y),
+        )
+    }
+}
+
+impl Debug for Rectangle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<Rectangle ({}, {}, {}, {})>",
+            &self.x1, &self.y1, &self.x2, &self.y2)
+    }
+}
+
+
+/// A rotation in radians, as stored after an object's location.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Default)]
 pub struct Orient {
-    pitch: f64,
-    roll: f64,
-    yaw: f64
+    pub pitch: f32,
+    pub roll: f32,
+    pub yaw: f32
+}
+
+// Three `f32`s with `repr(C)`: no padding, and every bit pattern is valid.
+unsafe impl bytemuck::Zeroable for Orient {}
+unsafe impl bytemuck::Pod for Orient {}
+
+impl Orient {
+    pub fn new(pitch: f32, roll: f32, yaw: f32) -> Self {
+        Self { pitch, roll, yaw }
+    }
+
+    /// The rotation facing from `from` towards `to`, without roll.
+    pub fn looking_at(from: &XYZ, to: &XYZ) -> Self {
+        let offset = *to - *from;
+        let yaw = (-offset.x).atan2(-offset.y).rem_euclid(std::f32::consts::TAU);
+        let pitch = offset.z.atan2(offset.x.hypot(offset.y));
+
+        Self::new(pitch, 0.0, yaw)
+    }
+
+    /// Unit vector the rotation faces.
+    ///
+    /// A yaw of 0 faces -y and yaw turns towards -x, as the game rotates objects.
+    pub fn direction(&self) -> XYZ {
+        let horizontal = self.pitch.cos();
+        XYZ::new(-self.yaw.sin() * horizontal, -self.yaw.cos() * horizontal, self.pitch.sin())
+    }
 }
 
 impl Debug for Orient {
@@ -81,6 +234,36 @@ impl Debug for Orient {
 }
 
 
+/// The first visible top-level window owned by `process_id`, if it has one yet.
+pub fn get_pid_window(process_id: u32) -> Option<HWND> {
+    struct Search {
+        process_id: u32,
+        found: HWND,
+    }
+
+    unsafe extern "system" fn check_window(window: HWND, search: LPARAM) -> BOOL {
+        let search = &mut *(search as *mut Search);
+        let mut window_process_id: DWORD = 0;
+        GetWindowThreadProcessId(window, &mut window_process_id);
+
+        if window_process_id == search.process_id && IsWindowVisible(window) != 0 {
+            search.found = window;
+            return FALSE
+        }
+
+        TRUE
+    }
+
+    let mut search = Search { process_id, found: null_mut() };
+    unsafe { EnumWindows(Some(check_window), &mut search as *mut Search as LPARAM) };
+
+    match search.found.is_null() {
+        true => None,
+        false => Some(search.found)
+    }
+}
+
+
 /// Analogue for pymem.module_from_name()
 pub fn module_from_name(process_id: u32, module_name: &str) -> Option<MODULEENTRY32> {
     unsafe {
@@ -127,12 +310,12 @@ pub fn get_wiz_install(path: Option<&str>) -> Result<PathBuf> {
     for path_str in install_paths {
         let path = PathBuf::from(path_str);
 
-        if path.join(r"\Wizard101.exe").exists() {
+        if path.join("Wizard101.exe").exists() {
             return Ok(path)
         }
     }
 
-    return Err(anyhow!("Unable to find Wizard101 installation in default or Steam install directories."))
+    return Err(WizWalkerError::InstallNotFound)
 }
 
 
@@ -150,7 +333,7 @@ pub fn parse_template_id_file(file_data: Vec<u8>) -> Result<HashMap<i32, String>
     let file_data_str = String::from_utf8(file_data.clone())?;
 
     if !file_data_str.starts_with("BINd") {
-        return Err(anyhow!("No BINd id string"))
+        return Err(WizWalkerError::ParseFailed { file: "TemplateManifest.xml".to_string(), reason: "No BINd id string".to_string() })
     }
 
     let mut decoder = ZlibDecoder::new(file_data.as_slice());